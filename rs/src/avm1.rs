@@ -5,42 +5,75 @@ use ::scoped_gc::{Gc, GcRefCell};
 use avm1_tree as avm1;
 use scoped_gc::{GcAllocErr, GcScope};
 
-use crate::context::{AvmResult, ContextImpl};
-use crate::error::{ReferenceToUndeclaredVariableWarning, TargetHasNoProperty, Warning};
-use crate::host::Host;
+use crate::context::{AvmResult, CallType, ContextImpl};
+use crate::error::{CorruptDataWarning, ReferenceToUndeclaredVariableWarning, TargetHasNoProperty, UncaughtExceptionWarning, UnknownActionWarning, Warning};
+use crate::host::{DragConstraint, FrameScript, Host};
 use crate::values::{AvmConvert, AvmNumber, AvmObject, AvmPrimitive, AvmString, AvmValue, ToPrimitiveHint};
-use crate::values::object::{AvmFunction, AvmObjectRef, AvmCallable};
-use crate::realm::Realm;
+use crate::values::object::{AvmCallable, AvmFunction, AvmFunctionFlags, AvmFunctionParameter, AvmObjectClass, AvmObjectPrototype, AvmObjectRef, DONT_ENUM};
+use crate::realm::{link_constructor, Realm};
+use crate::realm::array::new_array;
+use crate::realm::string::string_length;
+use crate::timer::{TimerCallback, TimerQueue};
+
+/// Number of prototypes visited by Flash Player when looking up a property.
+pub const DEFAULT_MAX_PROTOTYPE_DEPTH: usize = 255;
 
 pub struct Vm<'gc> {
-  gc: &'gc GcScope<'gc>,
+  pub(crate) gc: &'gc GcScope<'gc>,
 
-  realm: Realm<'gc>,
+  pub(crate) realm: Realm<'gc>,
 
   pub swf_version: u8,
 
+  /// Maximum number of prototypes visited by property lookups, deeper objects are ignored
+  pub max_prototype_depth: usize,
+
   // This is wrong: the pool may be dropped AFTER the GcScope
   pool: ConstantPool<'gc>,
 
-  host: &'gc dyn Host,
+  pub(crate) host: &'gc dyn Host,
 
   next_script_id: Avm1ScriptId,
   scripts_by_id: HashMap<Avm1ScriptId, Avm1Script>,
+
+  /// Outermost scope, exposing the properties of `_global` to all the scripts
+  global_scope: Gc<'gc, GcRefCell<Scope<'gc>>>,
+
+  /// Variables of each timeline, shared by all the scripts running on the same target.
+  timeline_scopes: HashMap<Option<TargetId>, Gc<'gc, GcRefCell<Scope<'gc>>>>,
+
+  /// Classes associated with library symbols by `Object.registerClass`
+  pub(crate) registered_classes: HashMap<String, AvmObjectRef<'gc>>,
+
+  /// Timers created by `setInterval` and `setTimeout`
+  pub(crate) timers: TimerQueue<'gc>,
 }
 
 impl<'gc> Vm<'gc> {
   pub fn new(gc: &'gc GcScope<'gc>, host: &'gc dyn Host, swf_version: u8) -> Self {
+    let realm = Realm::new(gc);
+    let global_scope = gc.alloc(GcRefCell::new(Scope::global(realm.global.clone()))).unwrap();
     Self {
       gc,
-      realm: Realm::new(gc),
+      realm,
       swf_version,
+      max_prototype_depth: DEFAULT_MAX_PROTOTYPE_DEPTH,
       pool: ConstantPool::new(),
       host,
       next_script_id: Avm1ScriptId(0),
       scripts_by_id: HashMap::new(),
+      global_scope,
+      timeline_scopes: HashMap::new(),
+      registered_classes: HashMap::new(),
+      timers: TimerQueue::new(),
     }
   }
 
+  /// Returns the class registered for the library symbol `name` with `Object.registerClass`.
+  pub fn registered_class(&self, name: &str) -> Option<AvmObjectRef<'gc>> {
+    self.registered_classes.get(name).cloned()
+  }
+
   pub fn create_script(&mut self, code: Vec<u8>, uri: Option<String>, target: Option<TargetId>) -> Avm1ScriptId {
     let id: Avm1ScriptId = self.next_script_id;
     self.next_script_id = Avm1ScriptId(id.0 + 1);
@@ -62,27 +95,380 @@ impl<'gc> Vm<'gc> {
       call_result: AvmValue::UNDEFINED,
       stack: Stack::new(),
       registers: RegisterTable::new(4),
-      scope: self.gc.alloc(GcRefCell::new(Scope::empty())).unwrap(),
+      scope: self.timeline_scope(script.target),
+      target: script.target,
       parent: None,
+      try_blocks: Vec::new(),
     };
 
     let mut ectx = ExecutionContext::new(self, frame);
+    if let Err(exception) = ectx.run() {
+      self.report_uncaught_exception(exception);
+    }
+  }
 
-    const MAX_ACTIONS: usize = 1000;
-    for _ in 0..MAX_ACTIONS {
-      let has_advanced = ectx.next();
-      if !has_advanced {
-        break;
+  /// Advances the virtual clock of the timers by `ms` milliseconds, running the due callbacks.
+  ///
+  /// Callbacks run in chronological order, timers due at the same time run in registration order.
+  /// An interval runs at most once per call, even if several periods elapsed. Negative, infinite and NaN durations are
+  /// ignored: the clock never goes backwards.
+  pub fn advance_time(&mut self, ms: f64) -> () {
+    if !ms.is_finite() || ms < 0f64 {
+      return;
+    }
+    let end = self.timers.time() + ms;
+    while let Some(timer) = self.timers.pop_due(end) {
+      let (callback, this_arg) = match timer.callback {
+        TimerCallback::Function(func) => (AvmValue::Object(func), AvmValue::UNDEFINED),
+        TimerCallback::Method(obj, name) => {
+          let this_arg = AvmValue::Object(obj);
+          match self.get_member(&this_arg, &name) {
+            Ok(method) => (method.unwrap_or(AvmValue::UNDEFINED), this_arg),
+            Err(exception) => {
+              self.report_uncaught_exception(exception);
+              continue;
+            }
+          }
+        }
+      };
+      if let Err(exception) = self.apply(callback, this_arg, &timer.args) {
+        self.report_uncaught_exception(exception);
+      }
+    }
+    self.timers.set_time(end);
+  }
+
+  /// Reports an exception that was not caught by any `Try` action to the host.
+  fn report_uncaught_exception(&mut self, exception: AvmValue<'gc>) -> () {
+    let mut ctx = ContextImpl { vm: self, this: AvmValue::UNDEFINED, args: Vec::new(), call_type: CallType::Apply };
+    // `toString` may throw again while reporting, the exception is then reported by its type
+    let value = match exception.to_avm_string(&mut ctx) {
+      Ok(value) => value.value().to_string(),
+      Err(_) => String::from("[type Object]"),
+    };
+    self.host.warn(&Warning::UncaughtException(UncaughtExceptionWarning { value }));
+  }
+
+  /// Calls `callable` with the provided `this` value and arguments.
+  ///
+  /// Calling a value that is not a function silently results in `undefined`.
+  pub fn apply(&mut self, callable: AvmValue<'gc>, this_arg: AvmValue<'gc>, args: &[AvmValue<'gc>]) -> AvmResult<'gc> {
+    self.call(callable, this_arg, None, args, CallType::Apply)
+  }
+
+  /// Calls `callable` with the `this` value `this_arg`.
+  ///
+  /// `base` is the object of the prototype chain of `this` holding the called method, the `super`
+  /// object of the call refers to its prototype. It defaults to the prototype of `this`.
+  fn call(&mut self, callable: AvmValue<'gc>, this_arg: AvmValue<'gc>, base: Option<AvmObjectRef<'gc>>, args: &[AvmValue<'gc>], call_type: CallType) -> AvmResult<'gc> {
+    let obj = match callable {
+      AvmValue::Object(obj) => obj,
+      _ => return Ok(AvmValue::UNDEFINED),
+    };
+    if let Some((super_this, super_base)) = as_super(&obj) {
+      // `super(...)` calls the constructor of the parent class on the current `this`
+      let ctr = obj.0.borrow().get("__constructor__").unwrap_or(AvmValue::UNDEFINED);
+      return self.call(ctr, super_this, super_base, args, call_type);
+    }
+    // Methods called through `super` run on the current `this`
+    let this_arg = match this_arg {
+      AvmValue::Object(ref this_obj) => as_super(this_obj).map_or(this_arg.clone(), |(super_this, _)| super_this),
+      this_arg => this_arg,
+    };
+    // The function object must not stay borrowed while it runs: it may modify itself
+    let callable = obj.0.borrow().callable.clone();
+    let func = match callable {
+      Some(AvmCallable::AvmFunction(func)) => func,
+      Some(AvmCallable::HostFunction(host_fn)) => {
+        let mut ctx = ContextImpl { vm: self, this: this_arg, args: args.to_vec(), call_type };
+        return (host_fn.func)(&mut ctx);
+      }
+      None => return Ok(AvmValue::UNDEFINED),
+    };
+
+    let flags: &AvmFunctionFlags = &func.flags;
+    let mut scope = Scope::child(Gc::clone(&func.scope));
+    let mut registers = RegisterTable::new(func.register_count);
+    // Values stored in the registers following register `0`
+    let mut preloaded: Vec<AvmValue<'gc>> = Vec::new();
+    if !flags.suppress_this {
+      scope.set_local(String::from("this"), this_arg.clone());
+    }
+    if flags.preload_this {
+      preloaded.push(this_arg.clone());
+    }
+    if flags.preload_arguments || !flags.suppress_arguments {
+      let arguments = AvmValue::Object(self.new_arguments(&obj, args));
+      if !flags.suppress_arguments {
+        scope.set_local(String::from("arguments"), arguments.clone());
+      }
+      if flags.preload_arguments {
+        preloaded.push(arguments);
+      }
+    }
+    if flags.preload_super || !flags.suppress_super {
+      let super_obj = self.new_super(&this_arg, base);
+      if !flags.suppress_super {
+        scope.set_local(String::from("super"), super_obj.clone());
+      }
+      if flags.preload_super {
+        preloaded.push(super_obj);
+      }
+    }
+    if flags.preload_root {
+      preloaded.push(self.get_scope_variable(&func.scope, "_root")?.unwrap_or(AvmValue::UNDEFINED));
+    }
+    if flags.preload_parent {
+      preloaded.push(self.get_scope_variable(&func.scope, "_parent")?.unwrap_or(AvmValue::UNDEFINED));
+    }
+    if flags.preload_global {
+      preloaded.push(AvmValue::Object(self.realm.global.clone()));
+    }
+    for (register, value) in (1..func.register_count).zip(preloaded) {
+      registers.set(register, value);
+    }
+    for (i, parameter) in func.parameters.iter().enumerate() {
+      let arg = args.get(i).cloned().unwrap_or(AvmValue::UNDEFINED);
+      match parameter.register {
+        Some(register) => registers.set(register, arg),
+        None => scope.set_local(parameter.name.clone(), arg),
+      }
+    }
+    let scope = self.gc.alloc(GcRefCell::new(scope)).unwrap();
+
+    let frame: CallFrame = CallFrame {
+      code: &func.code,
+      ip: 0,
+      this: this_arg,
+      call_result: AvmValue::UNDEFINED,
+      stack: Stack::new(),
+      registers,
+      scope,
+      target: func.target,
+      parent: None,
+      try_blocks: Vec::new(),
+    };
+
+    let mut ectx = ExecutionContext::new(self, frame);
+    ectx.run()?;
+
+    Ok(ectx.frame.call_result.clone())
+  }
+
+  /// Reads the property `key` of `target`, calling its getter for accessor properties.
+  ///
+  /// Returns `Ok(None)` if the property does not exist, and `Err` if a callback throws.
+  pub(crate) fn get_member(&mut self, target: &AvmValue<'gc>, key: &str) -> Result<Option<AvmValue<'gc>>, AvmValue<'gc>> {
+    let property = match target {
+      AvmValue::Null(_) => None,
+      AvmValue::Object(ref avm_object) => avm_object.0.borrow().find_property(key, self.swf_version, self.max_prototype_depth),
+      AvmValue::Undefined(_) => None,
+      // Primitives are boxed: their members are read from the prototype of their wrapper class
+      AvmValue::Boolean(_) => self.realm.boolean.boolean_p.0.borrow().find_property(key, self.swf_version, self.max_prototype_depth),
+      AvmValue::Number(_) => self.realm.number.number_p.0.borrow().find_property(key, self.swf_version, self.max_prototype_depth),
+      AvmValue::String(ref value) if key == "length" => {
+        return Ok(Some(AvmValue::number(string_length(value.value()) as f64)));
       }
+      AvmValue::String(_) => self.realm.string.string_p.0.borrow().find_property(key, self.swf_version, self.max_prototype_depth),
+    };
+    let property = match property {
+      Some(property) => property,
+      None => return self.resolve_missing_member(target, key),
+    };
+    match property.accessor {
+      Some(accessor) => self.apply(AvmValue::Object(accessor.getter), target.clone(), &[]).map(Some),
+      None => Ok(Some(property.value)),
+    }
+  }
+
+  /// Creates a new object with the constructor `ctr` (`new` operator).
+  ///
+  /// Native constructors may return their own object, the result of AVM constructors is ignored.
+  pub(crate) fn construct(&mut self, ctr: AvmValue<'gc>, args: &[AvmValue<'gc>]) -> AvmResult<'gc> {
+    let ctr_obj = match ctr {
+      AvmValue::Object(ref ctr_obj) if ctr_obj.0.borrow().callable.is_some() => ctr_obj.clone(),
+      _ => return Ok(AvmValue::UNDEFINED),
+    };
+    let prototype = match self.get_member(&ctr, "prototype")? {
+      Some(AvmValue::Object(prototype)) => prototype,
+      _ => self.realm.obj_p.clone(),
+    };
+    let this_obj = AvmObject::new(self.gc, Some(prototype.clone())).unwrap();
+    this_obj.0.borrow_mut().define(String::from("__constructor__"), ctr.clone(), DONT_ENUM);
+    let this_arg = AvmValue::Object(this_obj);
+
+    let is_host_function = matches!(ctr_obj.0.borrow().callable, Some(AvmCallable::HostFunction(_)));
+    let result = self.call(ctr, this_arg.clone(), Some(prototype), args, CallType::Construct)?;
+    match result {
+      result @ AvmValue::Object(_) if is_host_function => Ok(result),
+      _ => Ok(this_arg),
     }
   }
+
+  /// Returns the object holding the own property `key`: `target` or one of its prototypes.
+  fn find_owner(&self, target: &AvmValue<'gc>, key: &str) -> Option<AvmObjectRef<'gc>> {
+    let target = match target {
+      AvmValue::Object(target) => target,
+      _ => return None,
+    };
+    if target.0.borrow().has_own_property(key, self.swf_version) {
+      return Some(target.clone());
+    }
+    let prototypes = target.0.borrow().prototypes(self.max_prototype_depth);
+    prototypes.into_iter().find(|p| p.0.borrow().has_own_property(key, self.swf_version))
+  }
+
+  /// Creates the `arguments` array of a call of `callee`.
+  fn new_arguments(&mut self, callee: &AvmObjectRef<'gc>, args: &[AvmValue<'gc>]) -> AvmObjectRef<'gc> {
+    let mut ctx = ContextImpl { vm: self, this: AvmValue::UNDEFINED, args: Vec::new(), call_type: CallType::Apply };
+    let arguments = new_array(&mut ctx, args.to_vec());
+    arguments.0.borrow_mut().define(String::from("callee"), AvmValue::Object(callee.clone()), DONT_ENUM);
+    arguments
+  }
+
+  /// Creates the `super` object of a call with the `this` value `this_arg` (`undefined` if `this`
+  /// is not an object).
+  ///
+  /// Members are read from the prototype of `base` (the parent class), calling the object calls
+  /// the constructor of the parent class, stored in `base.__constructor__` by `Extends`.
+  fn new_super(&mut self, this_arg: &AvmValue<'gc>, base: Option<AvmObjectRef<'gc>>) -> AvmValue<'gc> {
+    let base = match (base, this_arg) {
+      (Some(base), _) => base,
+      (None, AvmValue::Object(this_obj)) => match this_obj.0.borrow().prototype {
+        AvmObjectPrototype::Object(ref prototype) => prototype.clone(),
+        _ => return AvmValue::UNDEFINED,
+      },
+      (None, _) => return AvmValue::UNDEFINED,
+    };
+    let parent = match base.0.borrow().prototype {
+      AvmObjectPrototype::Object(ref parent) => Some(parent.clone()),
+      _ => None,
+    };
+    let ctr = base.0.borrow().get("__constructor__").unwrap_or(AvmValue::UNDEFINED);
+    let super_obj = AvmObject::new(self.gc, parent).unwrap();
+    {
+      let mut super_obj = super_obj.0.borrow_mut();
+      super_obj.class = AvmObjectClass::Super;
+      super_obj.value = Some(this_arg.clone());
+      super_obj.define(String::from("__constructor__"), ctr, DONT_ENUM);
+    }
+    AvmValue::Object(super_obj)
+  }
+
+  /// Returns the value of the closest variable named `name` visible from `scope`, or `None` if it
+  /// does not exist.
+  fn get_scope_variable(&mut self, scope: &Gc<'gc, GcRefCell<Scope<'gc>>>, name: &str) -> Result<Option<AvmValue<'gc>>, AvmValue<'gc>> {
+    match Scope::find(scope, name, self.swf_version, self.max_prototype_depth) {
+      Some(ScopeBinding::Variable(scope)) => Ok(scope.borrow().variables.get(name).cloned()),
+      Some(ScopeBinding::Property(object)) => self.get_member(&AvmValue::Object(object), name),
+      None => Ok(None),
+    }
+  }
+
+  /// Allocates the function object for `avm_fn`, with a fresh `prototype` object.
+  fn new_avm_function(&mut self, avm_fn: AvmFunction<'gc>) -> AvmObjectRef<'gc> {
+    let func = AvmObject::new_callable(self.gc, AvmCallable::AvmFunction(avm_fn)).unwrap();
+    func.0.borrow_mut().prototype = AvmObjectPrototype::Object(self.realm.func_p.clone());
+    let prototype = AvmObject::new(self.gc, Some(self.realm.obj_p.clone())).unwrap();
+    link_constructor(&func, &prototype);
+    func
+  }
+
+  /// Calls the `__resolve` method of `target` (if any) to provide the value of a missing member.
+  ///
+  /// This handles both property reads and method calls (the result is then called).
+  fn resolve_missing_member(&mut self, target: &AvmValue<'gc>, key: &str) -> Result<Option<AvmValue<'gc>>, AvmValue<'gc>> {
+    if key == "__resolve" {
+      return Ok(None);
+    }
+    let resolve = match self.get_member(target, "__resolve")? {
+      Some(AvmValue::Object(resolve)) => resolve,
+      _ => return Ok(None),
+    };
+    if resolve.0.borrow().callable.is_none() {
+      return Ok(None);
+    }
+    let key = AvmValue::string(self.gc, key.to_string()).unwrap();
+    self.apply(AvmValue::Object(resolve), target.clone(), &[key]).map(Some)
+  }
+
+  /// Assigns the property `key` of `target`, calling its setter for accessor properties.
+  ///
+  /// Setting a member on a primitive value is silently ignored.
+  pub(crate) fn set_member(&mut self, target: &AvmValue<'gc>, key: String, value: AvmValue<'gc>) -> Result<(), AvmValue<'gc>> {
+    let avm_object = match target {
+      AvmValue::Object(ref avm_object) => avm_object,
+      _ => return Ok(()),
+    };
+    let accessor = avm_object.0.borrow()
+      .find_property(&key, self.swf_version, self.max_prototype_depth)
+      .and_then(|property| property.accessor);
+    let value = self.call_watcher(avm_object, &key, value)?;
+    match accessor {
+      // Accessors without setter are read-only
+      Some(accessor) => if let Some(setter) = accessor.setter {
+        self.apply(AvmValue::Object(setter), target.clone(), &[value])?;
+      },
+      None => avm_object.0.borrow_mut().set(key, value),
+    }
+    Ok(())
+  }
+
+  /// Calls the watcher of the property `key` (if any) and returns the value to store.
+  ///
+  /// The callback receives `(key, oldValue, newValue, userData)`.
+  fn call_watcher(&mut self, avm_object: &AvmObjectRef<'gc>, key: &str, value: AvmValue<'gc>) -> AvmResult<'gc> {
+    let watcher = match avm_object.0.borrow().watchers.get(key) {
+      Some(watcher) if !watcher.running => watcher.clone(),
+      _ => return Ok(value),
+    };
+    let old_value = avm_object.0.borrow().get(key).unwrap_or(AvmValue::UNDEFINED);
+    let args = [AvmValue::string(self.gc, key.to_string()).unwrap(), old_value, value, watcher.user_data];
+    self.set_watcher_running(avm_object, key, true);
+    let result = self.apply(AvmValue::Object(watcher.callback), AvmValue::Object(avm_object.clone()), &args);
+    self.set_watcher_running(avm_object, key, false);
+    result
+  }
+
+  fn set_watcher_running(&mut self, avm_object: &AvmObjectRef<'gc>, key: &str, running: bool) -> () {
+    // The callback may have removed its own watcher
+    if let Some(watcher) = avm_object.0.borrow_mut().watchers.get_mut(key) {
+      watcher.running = running;
+    }
+  }
+
+  /// Returns the scope holding the variables of the timeline of `target`.
+  ///
+  /// Timeline scopes are children of the scope of `_global`.
+  fn timeline_scope(&mut self, target: Option<TargetId>) -> Gc<'gc, GcRefCell<Scope<'gc>>> {
+    let gc = self.gc;
+    let global_scope = &self.global_scope;
+    let scope = self.timeline_scopes
+      .entry(target)
+      .or_insert_with(|| gc.alloc(GcRefCell::new(Scope::child(Gc::clone(global_scope)))).unwrap());
+    Gc::clone(scope)
+  }
+}
+
+/// Returns the `this` value and the base prototype of `obj` if it is a `super` object.
+fn as_super<'gc>(obj: &AvmObjectRef<'gc>) -> Option<(AvmValue<'gc>, Option<AvmObjectRef<'gc>>)> {
+  let obj = obj.0.borrow();
+  if obj.class != AvmObjectClass::Super {
+    return None;
+  }
+  let base = match obj.prototype {
+    AvmObjectPrototype::Object(ref prototype) => Some(prototype.clone()),
+    _ => None,
+  };
+  Some((obj.value.clone().unwrap_or(AvmValue::UNDEFINED), base))
 }
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone, Hash, Ord, PartialOrd, Trace)]
 pub struct Avm1ScriptId(usize);
 
-#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash, Ord, PartialOrd)]
-pub struct TargetId(usize);
+/// Key identifying a target (movie clip) of the host.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash, Ord, PartialOrd, Trace)]
+pub struct TargetId(pub usize);
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub(crate) struct Avm1Script {
@@ -130,13 +516,17 @@ impl<'gc> ConstantPool<'gc> {
 #[derive(Debug, Trace)]
 pub struct Scope<'gc> {
   variables: HashMap<String, AvmValue<'gc>>,
+  /// Object whose properties are exposed as variables (`_global`)
+  object: Option<AvmObjectRef<'gc>>,
   parent: Option<Gc<'gc, GcRefCell<Scope<'gc>>>>,
 }
 
 impl<'gc> Scope<'gc> {
-  fn empty() -> Self {
+  /// Creates the outermost scope, exposing the properties of `global`.
+  fn global(global: AvmObjectRef<'gc>) -> Self {
     Self {
       variables: HashMap::new(),
+      object: Some(global),
       parent: None,
     }
   }
@@ -144,6 +534,7 @@ impl<'gc> Scope<'gc> {
   fn child(parent: Gc<'gc, GcRefCell<Scope<'gc>>>) -> Self {
     Self {
       variables: HashMap::new(),
+      object: None,
       parent: Some(parent),
     }
   }
@@ -152,21 +543,70 @@ impl<'gc> Scope<'gc> {
     self.variables.insert(name, value);
   }
 
-  fn set(&mut self, name: String, value: AvmValue<'gc>) -> () {
-    if self.variables.contains_key(&name) {
-      self.variables.insert(name, value);
-    } else if self.parent.is_some() {
-      unimplemented!("Set variable in parent scope");
-    } else {
-      self.variables.insert(name, value);
+  /// Declares a local variable initialized to `undefined`, unless it already exists in this scope.
+  fn declare_local(&mut self, name: String) -> () {
+    self.variables.entry(name).or_insert(AvmValue::UNDEFINED);
+  }
+
+  /// Returns the scope or object holding the closest variable named `name` visible to
+  /// `swf_version`, starting from `scope`.
+  ///
+  /// Variables backed by an object (`_global`) must be accessed through `Vm::get_member` and
+  /// `Vm::set_member`, so getters, setters and watchers run.
+  fn find(scope: &Gc<'gc, GcRefCell<Scope<'gc>>>, name: &str, swf_version: u8, max_depth: usize) -> Option<ScopeBinding<'gc>> {
+    let mut scope = Gc::clone(scope);
+    loop {
+      let parent = {
+        let current = scope.borrow();
+        if current.variables.contains_key(name) {
+          return Some(ScopeBinding::Variable(Gc::clone(&scope)));
+        }
+        if let Some(ref object) = current.object {
+          if object.0.borrow().has_property(name, swf_version, max_depth) {
+            return Some(ScopeBinding::Property(object.clone()));
+          }
+        }
+        current.parent.clone()
+      };
+      scope = parent?;
+    }
+  }
+
+  /// Creates a variable in the outermost scope that is not backed by an object (the timeline).
+  fn set_in_timeline(&mut self, name: String, value: AvmValue<'gc>) -> () {
+    match self.parent {
+      Some(ref parent) if parent.borrow().object.is_none() => parent.borrow_mut().set_in_timeline(name, value),
+      _ => {
+        self.variables.insert(name, value);
+      }
     }
   }
 
-  fn get(&self, name: &str) -> Option<AvmValue<'gc>> {
-    self.variables.get(name).map(|v| v.clone())
+  /// Deletes the closest variable named `name`, returns `true` if it existed.
+  fn delete(&mut self, name: &str) -> bool {
+    if self.variables.remove(name).is_some() {
+      return true;
+    }
+    if let Some(ref object) = self.object {
+      if object.0.borrow_mut().delete(name) {
+        return true;
+      }
+    }
+    match self.parent {
+      Some(ref parent) => parent.borrow_mut().delete(name),
+      None => false,
+    }
   }
 }
 
+/// Location of a variable found by `Scope::find`.
+enum ScopeBinding<'gc> {
+  /// Variable stored in the scope
+  Variable(Gc<'gc, GcRefCell<Scope<'gc>>>),
+  /// Property of the object backing a scope
+  Property(AvmObjectRef<'gc>),
+}
+
 struct Stack<'gc> (Vec<AvmValue<'gc>>);
 
 impl<'gc> Stack<'gc> {
@@ -185,6 +625,10 @@ impl<'gc> Stack<'gc> {
   pub fn push(&mut self, value: AvmValue<'gc>) {
     self.0.push(value);
   }
+
+  pub fn len(&self) -> usize {
+    self.0.len()
+  }
 }
 
 struct RegisterTable<'gc> (Vec<AvmValue<'gc>>);
@@ -216,24 +660,66 @@ pub struct CallFrame<'frame, 'gc: 'frame> {
   stack: Stack<'gc>,
   registers: RegisterTable<'gc>,
   scope: Gc<'gc, GcRefCell<Scope<'gc>>>,
+  // Target used for contextual actions such as `Call`
+  target: Option<TargetId>,
   parent: Option<&'frame CallFrame<'frame, 'gc>>,
+  /// Blocks of the `Try` actions containing the instruction pointer, innermost last
+  try_blocks: Vec<TryBlock<'gc>>,
+}
+
+/// Code ranges of a `Try` action: the try, catch and finally blocks follow each other.
+struct TryBlock<'gc> {
+  start: usize,
+  catch_start: usize,
+  finally_start: usize,
+  end: usize,
+  /// `None` if there is no catch block
+  catch_target: Option<CatchTarget>,
+  state: TryState,
+  /// Exception thrown again at the end of the finally block
+  pending: Option<AvmValue<'gc>>,
+}
+
+enum CatchTarget {
+  Register(u8),
+  Variable(String),
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+enum TryState {
+  Try,
+  Catch,
+  Finally,
 }
 
 pub struct ExecutionContext<'ectx, 'gc: 'ectx> {
   vm: &'ectx mut Vm<'gc>,
   frame: CallFrame<'ectx, 'gc>,
+  /// Exception thrown by the current action
+  exception: Option<AvmValue<'gc>>,
 }
 
-fn to_usize(value: AvmValue) -> Option<usize> {
-  match value {
-    AvmValue::Number(avm_number) => {
-      let value: f64 = avm_number.value();
-      if (usize::MIN as f64) <= value && value <= (usize::MAX as f64) && value == value.trunc() {
-        Some(value as usize)
-      } else {
-        None
-      }
-    }
+/// Returns the length of the action record at the start of `code`, including its header.
+///
+/// Returns `None` if the record is truncated.
+fn record_end(code: &[u8]) -> Option<usize> {
+  let end = match code {
+    [code, ..] if *code < 0x80 => 1,
+    [_, low, high, ..] => 3 + (usize::from(*low) | (usize::from(*high) << 8)),
+    _ => return None,
+  };
+  if end <= code.len() {
+    Some(end)
+  } else {
+    None
+  }
+}
+
+/// Returns the command name if `url` uses the (case-insensitive) `FSCommand:` pseudo-protocol.
+fn get_fs_command(url: &str) -> Option<&str> {
+  const PREFIX: &str = "FSCommand:";
+  match url.get(..PREFIX.len()) {
+    Some(prefix) if prefix.eq_ignore_ascii_case(PREFIX) => Some(&url[PREFIX.len()..]),
     _ => None,
   }
 }
@@ -243,20 +729,123 @@ impl<'ectx, 'gc: 'ectx> ExecutionContext<'ectx, 'gc> {
     Self {
       vm,
       frame,
+      exception: None,
+    }
+  }
+
+  /// Executes the actions of the current frame until it ends (or the action budget is exhausted).
+  ///
+  /// Returns the exception if the frame ends because of an uncaught exception.
+  pub fn run(&mut self) -> Result<(), AvmValue<'gc>> {
+    const MAX_ACTIONS: usize = 1000;
+    for _ in 0..MAX_ACTIONS {
+      let has_advanced = self.next()?;
+      if !has_advanced {
+        break;
+      }
     }
+    Ok(())
   }
 
   /// Executes the next step, returns a boolean `has_advanced`.
-  pub fn next(&mut self) -> bool {
+  pub fn next(&mut self) -> Result<bool, AvmValue<'gc>> {
+    self.leave_try_blocks()?;
     // TODO: Cleaner support for the `End` action
     if self.frame.ip >= self.frame.code.len() || self.frame.code[self.frame.ip] == 0 {
-      return false;
+      return Ok(false);
     }
 
-    let (input, action) = avm1_parser::parse_action(&self.frame.code[self.frame.ip..]).unwrap();
-    self.frame.ip = input.as_ptr() as usize - self.frame.code.as_ptr() as usize;
+    let start = self.frame.ip;
+    let end = match record_end(&self.frame.code[start..]) {
+      Some(end) => start + end,
+      None => {
+        // Truncated record (its declared length runs past the end): the player ends the script
+        self.vm.host.warn(&Warning::CorruptData(CorruptDataWarning));
+        self.frame.ip = self.frame.code.len();
+        return Ok(false);
+      }
+    };
+    // Actions with a length header always end at their declared length, even if their body
+    // has unused bytes. The body is only parsed within this length.
+    self.frame.ip = end;
+    let action = match avm1_parser::parse_action(&self.frame.code[start..end]) {
+      Ok((_, action)) => action,
+      Err(_) => {
+        // Malformed record: the player reports it and skips it
+        self.vm.host.warn(&Warning::CorruptData(CorruptDataWarning));
+        return Ok(true);
+      }
+    };
     self.exec(&action);
-    true
+    if let Some(exception) = self.exception.take() {
+      self.unwind(exception)?;
+    }
+    Ok(true)
+  }
+
+  /// Records `exception` as thrown by the current action.
+  fn throw(&mut self, exception: AvmValue<'gc>) -> () {
+    self.exception = Some(exception);
+  }
+
+  /// Moves to the handler of the innermost `Try` action, or returns the exception if the frame
+  /// has no handler left.
+  ///
+  /// A catch block receives the exception, a finally block without catch block runs before the
+  /// exception is thrown again.
+  fn unwind(&mut self, exception: AvmValue<'gc>) -> Result<(), AvmValue<'gc>> {
+    while let Some(block) = self.frame.try_blocks.pop() {
+      match block.state {
+        TryState::Try if block.catch_target.is_some() => {
+          match block.catch_target {
+            Some(CatchTarget::Register(register)) => self.frame.registers.set(register, exception),
+            Some(CatchTarget::Variable(ref name)) => self.frame.scope.borrow_mut().set_local(name.clone(), exception),
+            None => {}
+          }
+          self.frame.ip = block.catch_start;
+          self.frame.try_blocks.push(TryBlock { state: TryState::Catch, ..block });
+          return Ok(());
+        }
+        TryState::Try | TryState::Catch if block.finally_start < block.end => {
+          self.frame.ip = block.finally_start;
+          self.frame.try_blocks.push(TryBlock { state: TryState::Finally, pending: Some(exception), ..block });
+          return Ok(());
+        }
+        _ => {}
+      }
+    }
+    Err(exception)
+  }
+
+  /// Updates the `Try` blocks when the instruction pointer reaches the end of a block or jumps
+  /// out of them.
+  fn leave_try_blocks(&mut self) -> Result<(), AvmValue<'gc>> {
+    loop {
+      let ip = self.frame.ip;
+      let block = match self.frame.try_blocks.last_mut() {
+        Some(block) => block,
+        None => return Ok(()),
+      };
+      match block.state {
+        // The catch block only runs for exceptions
+        TryState::Try if ip == block.catch_start => {
+          self.frame.ip = block.finally_start;
+          block.state = TryState::Finally;
+        }
+        TryState::Catch if ip == block.finally_start => block.state = TryState::Finally,
+        TryState::Finally if ip == block.end => {
+          let pending = block.pending.take();
+          self.frame.try_blocks.pop();
+          if let Some(exception) = pending {
+            self.unwind(exception)?;
+          }
+        }
+        _ if ip < block.start || ip >= block.end => {
+          self.frame.try_blocks.pop();
+        }
+        _ => return Ok(()),
+      }
+    }
   }
 
   pub fn exec(&mut self, action: &avm1::Action) -> () {
@@ -271,33 +860,33 @@ impl<'ectx, 'gc: 'ectx> ExecutionContext<'ectx, 'gc> {
       &avm1::Action::BitRShift => unimplemented!("BitRShift"),
       &avm1::Action::BitURShift => unimplemented!("BitURShift"),
       &avm1::Action::BitXor => unimplemented!("BitXor"),
-      &avm1::Action::Call => unimplemented!("Call"),
+      &avm1::Action::Call => self.exec_call(),
       &avm1::Action::CallFunction => self.exec_call_function(),
-      &avm1::Action::CallMethod => unimplemented!("CallMethod"),
+      &avm1::Action::CallMethod => self.exec_call_method(),
       &avm1::Action::CastOp => unimplemented!("CastOp"),
       &avm1::Action::ConstantPool(ref constant_pool) => self.exec_constant_pool(constant_pool),
       &avm1::Action::CharToAscii => unimplemented!("CharToAscii"),
-      &avm1::Action::CloneSprite => unimplemented!("CloneSprite"),
-      &avm1::Action::Decrement => unimplemented!("Decrement"),
+      &avm1::Action::CloneSprite => self.exec_clone_sprite(),
+      &avm1::Action::Decrement => self.exec_decrement(),
       &avm1::Action::DefineFunction(ref action) => self.exec_define_function(action),
       &avm1::Action::DefineFunction2(ref action) => self.exec_define_function2(action),
       &avm1::Action::DefineLocal => self.exec_define_local(),
-      &avm1::Action::DefineLocal2 => unimplemented!("DefineLocal2"),
-      &avm1::Action::Delete => unimplemented!("Delete"),
-      &avm1::Action::Delete2 => unimplemented!("Delete2"),
+      &avm1::Action::DefineLocal2 => self.exec_define_local2(),
+      &avm1::Action::Delete => self.exec_delete(),
+      &avm1::Action::Delete2 => self.exec_delete2(),
       &avm1::Action::Divide => self.exec_divide(),
-      &avm1::Action::EndDrag => unimplemented!("EndDrag"),
-      &avm1::Action::Enumerate => unimplemented!("Enumerate"),
-      &avm1::Action::Enumerate2 => unimplemented!("Enumerate2"),
+      &avm1::Action::EndDrag => self.exec_end_drag(),
+      &avm1::Action::Enumerate => self.exec_enumerate(),
+      &avm1::Action::Enumerate2 => self.exec_enumerate2(),
       &avm1::Action::Equals => self.exec_equals(),
       &avm1::Action::Equals2 => self.exec_equals2(),
-      &avm1::Action::Extends => unimplemented!("Extends"),
-      &avm1::Action::FsCommand2 => unimplemented!("FsCommand2"),
+      &avm1::Action::Extends => self.exec_extends(),
+      &avm1::Action::FsCommand2 => self.exec_fs_command2(),
       &avm1::Action::GetMember => self.exec_get_member(),
       &avm1::Action::GetProperty => unimplemented!("GetProperty"),
-      &avm1::Action::GetTime => unimplemented!("GetTime"),
-      &avm1::Action::GetUrl(_) => unimplemented!("GetUrl"),
-      &avm1::Action::GetUrl2(_) => unimplemented!("GetUrl2"),
+      &avm1::Action::GetTime => self.exec_get_time(),
+      &avm1::Action::GetUrl(ref action) => self.exec_get_url(action),
+      &avm1::Action::GetUrl2(ref action) => self.exec_get_url2(action),
       &avm1::Action::GetVariable => self.exec_get_variable(),
       &avm1::Action::GotoFrame(_) => unimplemented!("GotoFrame"),
       &avm1::Action::GotoFrame2(_) => unimplemented!("GotoFrame2"),
@@ -316,9 +905,9 @@ impl<'ectx, 'gc: 'ectx> ExecutionContext<'ectx, 'gc> {
       &avm1::Action::MbCharToAscii => unimplemented!("MbCharToAscii"),
       &avm1::Action::MbStringExtract => unimplemented!("MbStringExtract"),
       &avm1::Action::MbStringLength => unimplemented!("MbStringLength"),
-      &avm1::Action::Modulo => unimplemented!("Modulo"),
+      &avm1::Action::Modulo => self.exec_modulo(),
       &avm1::Action::Multiply => self.exec_multiply(),
-      &avm1::Action::NewMethod => unimplemented!("NewMethod"),
+      &avm1::Action::NewMethod => self.exec_new_method(),
       &avm1::Action::NewObject => self.exec_new_object(),
       &avm1::Action::NextFrame => unimplemented!("NextFrame"),
       &avm1::Action::Not => self.exec_not(),
@@ -328,18 +917,18 @@ impl<'ectx, 'gc: 'ectx> ExecutionContext<'ectx, 'gc> {
       &avm1::Action::PrevFrame => unimplemented!("PrevFrame"),
       &avm1::Action::Push(ref push) => self.exec_push(push),
       &avm1::Action::PushDuplicate => self.exec_push_duplicate(),
-      &avm1::Action::RandomNumber => unimplemented!("RandomNumber"),
-      &avm1::Action::RemoveSprite => unimplemented!("RemoveSprite"),
-      &avm1::Action::Return => unimplemented!("Return"),
-      &avm1::Action::SetMember => unimplemented!("SetMember"),
+      &avm1::Action::RandomNumber => self.exec_random_number(),
+      &avm1::Action::RemoveSprite => self.exec_remove_sprite(),
+      &avm1::Action::Return => self.exec_return(),
+      &avm1::Action::SetMember => self.exec_set_member(),
       &avm1::Action::SetProperty => unimplemented!("SetProperty"),
       &avm1::Action::SetTarget(_) => unimplemented!("SetTarget"),
       &avm1::Action::SetTarget2 => unimplemented!("SetTarget2"),
       &avm1::Action::SetVariable => self.exec_set_variable(),
-      &avm1::Action::StackSwap => unimplemented!("StackSwap"),
-      &avm1::Action::StartDrag => unimplemented!("StartDrag"),
+      &avm1::Action::StackSwap => self.exec_stack_swap(),
+      &avm1::Action::StartDrag => self.exec_start_drag(),
       &avm1::Action::Stop => unimplemented!("Stop"),
-      &avm1::Action::StopSounds => unimplemented!("StopSounds"),
+      &avm1::Action::StopSounds => self.exec_stop_sounds(),
       &avm1::Action::StoreRegister(ref action) => self.exec_store_register(action),
       &avm1::Action::StrictEquals => self.exec_strict_equals(),
       &avm1::Action::StringAdd => self.exec_string_add(),
@@ -350,24 +939,33 @@ impl<'ectx, 'gc: 'ectx> ExecutionContext<'ectx, 'gc> {
       &avm1::Action::StringLess => unimplemented!("StringLess"),
       &avm1::Action::Subtract => self.exec_subtract(),
       &avm1::Action::TargetPath => unimplemented!("TargetPath"),
-      &avm1::Action::ToInteger => unimplemented!("ToInteger"),
-      &avm1::Action::ToNumber => unimplemented!("ToNumber"),
-      &avm1::Action::ToString => unimplemented!("ToString"),
-      &avm1::Action::ToggleQuality => unimplemented!("ToggleQuality"),
-      &avm1::Action::Throw => unimplemented!("Throw"),
+      &avm1::Action::ToInteger => self.exec_to_integer(),
+      &avm1::Action::ToNumber => self.exec_to_number(),
+      &avm1::Action::ToString => self.exec_to_string(),
+      &avm1::Action::ToggleQuality => self.exec_toggle_quality(),
+      &avm1::Action::Throw => self.exec_throw(),
       &avm1::Action::Trace => self.exec_trace(),
-      &avm1::Action::Try(_) => unimplemented!("Try"),
-      &avm1::Action::TypeOf => unimplemented!("TypeOf"),
+      &avm1::Action::Try(ref action) => self.exec_try(action),
+      &avm1::Action::TypeOf => self.exec_type_of(),
       &avm1::Action::WaitForFrame(_) => unimplemented!("WaitForFrame"),
       &avm1::Action::WaitForFrame2(_) => unimplemented!("WaitForFrame2"),
       &avm1::Action::With(_) => unimplemented!("With"),
-      &avm1::Action::Unknown(_) => unimplemented!("Unknown"),
+      // Unknown actions are skipped (the parser already consumed their declared length)
+      &avm1::Action::Unknown(ref action) => {
+        self.vm.host.warn(&Warning::UnknownAction(UnknownActionWarning { code: action.code }))
+      }
     }
   }
 
   fn exec_add(&mut self) -> () {
-    let right = self.frame.stack.pop().legacy_to_avm_number().value();
-    let left = self.frame.stack.pop().legacy_to_avm_number().value();
+    let right = match self.pop_number() {
+      Ok(right) => right,
+      Err(exception) => return self.throw(exception),
+    };
+    let left = match self.pop_number() {
+      Ok(left) => left,
+      Err(exception) => return self.throw(exception),
+    };
     self.frame.stack.push(AvmValue::number(left + right));
   }
 
@@ -376,12 +974,24 @@ impl<'ectx, 'gc: 'ectx> ExecutionContext<'ectx, 'gc> {
   fn exec_add2(&mut self) -> () {
     let right = self.frame.stack.pop();
     let left = self.frame.stack.pop();
-    let left = left.to_avm_primitive(&mut self.as_function_context(), ToPrimitiveHint::Default).unwrap();
-    let right = right.to_avm_primitive(&mut self.as_function_context(), ToPrimitiveHint::Default).unwrap();
+    let left = match left.to_avm_primitive(&mut self.as_function_context(), ToPrimitiveHint::Default) {
+      Ok(left) => left,
+      Err(exception) => return self.throw(exception),
+    };
+    let right = match right.to_avm_primitive(&mut self.as_function_context(), ToPrimitiveHint::Default) {
+      Ok(right) => right,
+      Err(exception) => return self.throw(exception),
+    };
     match (left, right) {
       (left @ AvmPrimitive::String(_), right) | (left, right @ AvmPrimitive::String(_)) => {
-        let left = left.to_avm_string(&mut self.as_function_context()).unwrap();
-        let right = right.to_avm_string(&mut self.as_function_context()).unwrap();
+        let left = match left.to_avm_string(&mut self.as_function_context()) {
+          Ok(left) => left,
+          Err(exception) => return self.throw(exception),
+        };
+        let right = match right.to_avm_string(&mut self.as_function_context()) {
+          Ok(right) => right,
+          Err(exception) => return self.throw(exception),
+        };
         let result = format!("{}{}", left.value(), right.value());
         self.frame.stack.push(AvmValue::String(AvmString::new(self.vm.gc, result).unwrap()));
       }
@@ -400,27 +1010,126 @@ impl<'ectx, 'gc: 'ectx> ExecutionContext<'ectx, 'gc> {
     self.frame.stack.push(AvmValue::legacy_boolean(left != 0f64 && right != 0f64, self.vm.swf_version));
   }
 
+  /// Runs the actions of another frame, identified by a label, number or path (e.g. `/a:3`).
+  ///
+  /// The frame script runs synchronously, on the timeline of its target and with its own
+  /// constant pool.
+  fn exec_call(&mut self) -> () {
+    let frame = self.frame.stack.pop();
+    let frame = match frame.to_avm_string(&mut self.as_function_context()) {
+      Ok(frame) => frame,
+      Err(exception) => return self.throw(exception),
+    };
+    let script: FrameScript = match self.vm.host.get_frame_script(self.frame.target, frame.value()) {
+      Some(script) => script,
+      None => return,
+    };
+
+    let scope = self.vm.timeline_scope(script.target);
+    let caller_pool = ::std::mem::replace(&mut self.vm.pool, ConstantPool::new());
+
+    let frame: CallFrame = CallFrame {
+      code: &script.code,
+      ip: 0,
+      this: AvmValue::UNDEFINED,
+      call_result: AvmValue::UNDEFINED,
+      stack: Stack::new(),
+      registers: RegisterTable::new(4),
+      scope,
+      target: script.target,
+      parent: Some(&self.frame),
+      try_blocks: Vec::new(),
+    };
+
+    let mut ectx = ExecutionContext::new(self.vm, frame);
+    let result = ectx.run();
+
+    self.vm.pool = caller_pool;
+    if let Err(exception) = result {
+      self.throw(exception);
+    }
+  }
+
   fn exec_call_function(&mut self) -> () {
     let func_name = self.frame.stack.pop();
     let arg_count = self.frame.stack.pop();
 
-    let func_name = func_name.to_avm_string(&mut self.as_function_context()).unwrap();
-    let arg_count = arg_count.to_avm_number();
+    let func_name = match func_name.to_avm_string(&mut self.as_function_context()) {
+      Ok(func_name) => func_name,
+      Err(exception) => return self.throw(exception),
+    };
+    let args = match self.pop_args(arg_count) {
+      Ok(args) => args,
+      Err(exception) => return self.throw(exception),
+    };
 
-    if arg_count.value() != 0f64 {
-      unimplemented!("CallFunction with non-zero arg count")
+    let func = match self.get_variable(func_name.value()) {
+      Ok(func) => func.unwrap_or(AvmValue::UNDEFINED),
+      Err(exception) => return self.throw(exception),
+    };
+
+    // Calling something that is not a function silently results in `undefined`
+    match self.apply(func, AvmValue::UNDEFINED, &args) {
+      Ok(result) => self.frame.stack.push(result),
+      Err(exception) => self.throw(exception),
     }
+  }
 
-    let func = self.frame.scope.borrow().get(func_name.value()).unwrap_or(AvmValue::UNDEFINED);
+  fn exec_clone_sprite(&mut self) -> () {
+    let depth = match self.pop_number() {
+      Ok(depth) => depth as i32,
+      Err(exception) => return self.throw(exception),
+    };
+    let name = self.frame.stack.pop();
+    let target = self.frame.stack.pop();
+    let name = match name.to_avm_string(&mut self.as_function_context()) {
+      Ok(name) => name,
+      Err(exception) => return self.throw(exception),
+    };
+    let target = match target.to_avm_string(&mut self.as_function_context()) {
+      Ok(target) => target,
+      Err(exception) => return self.throw(exception),
+    };
+    self.vm.host.clone_sprite(target.value(), name.value(), depth);
+  }
 
-    let result = self.apply(func, AvmValue::UNDEFINED, &[]);
+  /// Calls the method `key` of an object, or the object itself if the key is empty or undefined.
+  ///
+  /// If the method does not exist or is not callable, the result is `undefined`.
+  fn exec_call_method(&mut self) -> () {
+    let key = self.frame.stack.pop();
+    let target = self.frame.stack.pop();
+    let arg_count = self.frame.stack.pop();
+    let args = match self.pop_args(arg_count) {
+      Ok(args) => args,
+      Err(exception) => return self.throw(exception),
+    };
 
-    let result = match result {
-      Ok(result) => result,
-      Err(_) => unimplemented!("Throw support"),
+    let (method, this_arg, base) = match key {
+      AvmValue::Undefined(_) => (target, AvmValue::UNDEFINED, None),
+      key => {
+        let key = match key.to_avm_string(&mut self.as_function_context()) {
+          Ok(key) => key,
+          Err(exception) => return self.throw(exception),
+        };
+        if key.value().is_empty() {
+          (target, AvmValue::UNDEFINED, None)
+        } else {
+          match self.get_member(&target, key.value()) {
+            Ok(method) => {
+              let base = self.vm.find_owner(&target, key.value());
+              (method.unwrap_or(AvmValue::UNDEFINED), target, base)
+            }
+            Err(exception) => return self.throw(exception),
+          }
+        }
+      }
     };
 
-    self.frame.stack.push(result);
+    match self.vm.call(method, this_arg, base, &args, CallType::Apply) {
+      Ok(result) => self.frame.stack.push(result),
+      Err(exception) => self.throw(exception),
+    }
   }
 
   fn exec_constant_pool(&mut self, constant_pool: &avm1::actions::ConstantPool) -> () {
@@ -431,22 +1140,34 @@ impl<'ectx, 'gc: 'ectx> ExecutionContext<'ectx, 'gc> {
     self.vm.pool.set(pool);
   }
 
+  fn exec_decrement(&mut self) -> () {
+    let arg = match self.pop_number() {
+      Ok(arg) => arg,
+      Err(exception) => return self.throw(exception),
+    };
+    self.frame.stack.push(AvmValue::number(arg - 1f64))
+  }
+
   fn exec_define_function(&mut self, action: &avm1::actions::DefineFunction) -> () {
     let start = self.frame.ip;
-    let end = start + usize::from(action.body_size);
+    let end = (start + usize::from(action.body_size)).min(self.frame.code.len());
     let code = self.frame.code[start..end].to_vec();
 
-    if !action.parameters.is_empty() {
-      unimplemented!("DefineFunction with non-empty `parameters`");
-    }
+    let parameters: Vec<AvmFunctionParameter> = action.parameters
+      .iter()
+      .map(|name| AvmFunctionParameter { name: name.clone(), register: None })
+      .collect();
 
     let avm_fn = AvmFunction {
       code,
       scope: Gc::clone(&self.frame.scope),
       register_count: 4,
+      parameters,
+      target: self.frame.target,
+      flags: AvmFunctionFlags::default(),
     };
 
-    let avm_obj = AvmObject::new_callable(self.vm.gc, AvmCallable::AvmFunction(avm_fn)).unwrap();
+    let avm_obj = self.vm.new_avm_function(avm_fn);
     let value = AvmValue::Object(avm_obj);
 
     if !action.name.is_empty() {
@@ -459,32 +1180,40 @@ impl<'ectx, 'gc: 'ectx> ExecutionContext<'ectx, 'gc> {
 
   fn exec_define_function2(&mut self, action: &avm1::actions::DefineFunction2) -> () {
     let start = self.frame.ip;
-    let end = start + usize::from(action.body_size);
+    let end = (start + usize::from(action.body_size)).min(self.frame.code.len());
     let code = self.frame.code[start..end].to_vec();
 
-    if !action.parameters.is_empty() {
-      unimplemented!("DefineFunction2 with non-empty `parameters`");
-    }
+    // Register `0` means that the parameter is not stored in a register
+    let parameters: Vec<AvmFunctionParameter> = action.parameters
+      .iter()
+      .map(|p| AvmFunctionParameter {
+        name: p.name.clone(),
+        register: if p.register == 0 { None } else { Some(p.register) },
+      })
+      .collect();
 
-    if action.preload_this
-      || action.suppress_this
-      || action.preload_arguments
-      || action.suppress_arguments
-      || action.preload_super
-      || action.suppress_super
-      || action.preload_root
-      || action.preload_parent
-      || action.preload_global {
-      unimplemented!("DefineFunction2 with register flags");
-    }
+    let flags = AvmFunctionFlags {
+      preload_this: action.preload_this,
+      suppress_this: action.suppress_this,
+      preload_arguments: action.preload_arguments,
+      suppress_arguments: action.suppress_arguments,
+      preload_super: action.preload_super,
+      suppress_super: action.suppress_super,
+      preload_root: action.preload_root,
+      preload_parent: action.preload_parent,
+      preload_global: action.preload_global,
+    };
 
     let avm_fn = AvmFunction {
       code,
       scope: Gc::clone(&self.frame.scope),
       register_count: action.register_count as u8,
+      parameters,
+      target: self.frame.target,
+      flags,
     };
 
-    let avm_obj = AvmObject::new_callable(self.vm.gc, AvmCallable::AvmFunction(avm_fn)).unwrap();
+    let avm_obj = self.vm.new_avm_function(avm_fn);
     let value = AvmValue::Object(avm_obj);
 
     if !action.name.is_empty() {
@@ -498,13 +1227,55 @@ impl<'ectx, 'gc: 'ectx> ExecutionContext<'ectx, 'gc> {
   fn exec_define_local(&mut self) -> () {
     let value = self.frame.stack.pop();
     let name = self.frame.stack.pop();
-    let name = name.to_avm_string(&mut self.as_function_context()).unwrap();
+    let name = match name.to_avm_string(&mut self.as_function_context()) {
+      Ok(name) => name,
+      Err(exception) => return self.throw(exception),
+    };
     self.frame.scope.borrow_mut().set_local(name.value().to_owned(), value);
   }
 
+  fn exec_define_local2(&mut self) -> () {
+    let name = self.frame.stack.pop();
+    let name = match name.to_avm_string(&mut self.as_function_context()) {
+      Ok(name) => name,
+      Err(exception) => return self.throw(exception),
+    };
+    self.frame.scope.borrow_mut().declare_local(name.value().to_owned());
+  }
+
+  fn exec_delete(&mut self) -> () {
+    let key = self.frame.stack.pop();
+    let target = self.frame.stack.pop();
+    let key = match key.to_avm_string(&mut self.as_function_context()) {
+      Ok(key) => key,
+      Err(exception) => return self.throw(exception),
+    };
+    let deleted = match target {
+      AvmValue::Object(ref avm_object) => avm_object.0.borrow_mut().delete(key.value()),
+      _ => false,
+    };
+    self.frame.stack.push(AvmValue::boolean(deleted));
+  }
+
+  fn exec_delete2(&mut self) -> () {
+    let name = self.frame.stack.pop();
+    let name = match name.to_avm_string(&mut self.as_function_context()) {
+      Ok(name) => name,
+      Err(exception) => return self.throw(exception),
+    };
+    let deleted = self.frame.scope.borrow_mut().delete(name.value());
+    self.frame.stack.push(AvmValue::boolean(deleted));
+  }
+
   fn exec_divide(&mut self) -> () {
-    let right = self.frame.stack.pop().legacy_to_avm_number().value();
-    let left = self.frame.stack.pop().legacy_to_avm_number().value();
+    let right = match self.pop_number() {
+      Ok(right) => right,
+      Err(exception) => return self.throw(exception),
+    };
+    let left = match self.pop_number() {
+      Ok(left) => left,
+      Err(exception) => return self.throw(exception),
+    };
     if right == 0f64 && self.vm.swf_version < 5 {
       self.frame.stack.push(AvmValue::String(AvmString::new(self.vm.gc, String::from("#ERROR#")).unwrap()))
     } else {
@@ -512,6 +1283,40 @@ impl<'ectx, 'gc: 'ectx> ExecutionContext<'ectx, 'gc> {
     }
   }
 
+  fn exec_end_drag(&mut self) -> () {
+    self.vm.host.stop_drag();
+  }
+
+  fn exec_enumerate(&mut self) -> () {
+    let name = self.frame.stack.pop();
+    let name = match name.to_avm_string(&mut self.as_function_context()) {
+      Ok(name) => name,
+      Err(exception) => return self.throw(exception),
+    };
+    let target = match self.get_variable(name.value()) {
+      Ok(target) => target.unwrap_or(AvmValue::UNDEFINED),
+      Err(exception) => return self.throw(exception),
+    };
+    self.enumerate(target);
+  }
+
+  fn exec_enumerate2(&mut self) -> () {
+    let target = self.frame.stack.pop();
+    self.enumerate(target);
+  }
+
+  /// Pushes `null` followed by the enumerable keys of `target`, the first key ends at the top.
+  fn enumerate(&mut self, target: AvmValue<'gc>) -> () {
+    let keys: Vec<String> = match target {
+      AvmValue::Object(ref obj) => obj.0.borrow().enumerable_keys(self.vm.swf_version, self.vm.max_prototype_depth),
+      _ => Vec::new(),
+    };
+    self.frame.stack.push(AvmValue::NULL);
+    for key in keys.into_iter().rev() {
+      self.frame.stack.push(AvmValue::string(self.vm.gc, key).unwrap());
+    }
+  }
+
   fn exec_equals(&mut self) -> () {
     let right = self.frame.stack.pop().legacy_to_avm_number().value();
     let left = self.frame.stack.pop().legacy_to_avm_number().value();
@@ -522,13 +1327,23 @@ impl<'ectx, 'gc: 'ectx> ExecutionContext<'ectx, 'gc> {
     let right = self.frame.stack.pop();
     let left = self.frame.stack.pop();
 
-    // Implementation of the AbstractEquals algorithm from ECMA 262-3, section 11.9.3
-    // This implementation removes recursion by handling each type combination manually
-    let result: bool = match (left, right) {
+    let result: bool = match self.abstract_equals(left, right) {
+      Ok(result) => result,
+      Err(exception) => return self.throw(exception),
+    };
+    self.frame.stack.push(AvmValue::boolean(result));
+  }
+
+  /// Implementation of the AbstractEquals algorithm from ECMA 262-3, section 11.9.3
+  ///
+  /// Each type combination is handled manually: only the comparisons between an object and a
+  /// primitive value recurse once, after converting the object to a primitive.
+  fn abstract_equals(&mut self, left: AvmValue<'gc>, right: AvmValue<'gc>) -> Result<bool, AvmValue<'gc>> {
+    let result = match (left, right) {
       (AvmValue::Boolean(l), AvmValue::Boolean(r)) => l.value() == r.value(),
       (AvmValue::Boolean(_), AvmValue::Null(_)) => false,
       (AvmValue::Boolean(l), AvmValue::Number(r)) => l.to_avm_number().value() == r.value(),
-      (AvmValue::Boolean(_), AvmValue::Object(_)) => unimplemented!("Boolean == Object"),
+      (l @ AvmValue::Boolean(_), AvmValue::Object(r)) => self.equals_primitive(l, r)?,
       (AvmValue::Boolean(l), AvmValue::String(r)) => l.to_avm_number().value() == r.to_avm_number().value(),
       (AvmValue::Boolean(_), AvmValue::Undefined(_)) => false,
       (AvmValue::Null(_), AvmValue::Boolean(_)) => false,
@@ -540,19 +1355,19 @@ impl<'ectx, 'gc: 'ectx> ExecutionContext<'ectx, 'gc> {
       (AvmValue::Number(l), AvmValue::Boolean(r)) => l.value() == r.to_avm_number().value(),
       (AvmValue::Number(_), AvmValue::Null(_)) => false,
       (AvmValue::Number(l), AvmValue::Number(r)) => l.value() == r.value(),
-      (AvmValue::Number(_), AvmValue::Object(_)) => unimplemented!("Number == Object"),
+      (l @ AvmValue::Number(_), AvmValue::Object(r)) => self.equals_primitive(l, r)?,
       (AvmValue::Number(l), AvmValue::String(r)) => l.value() == r.to_avm_number().value(),
       (AvmValue::Number(_), AvmValue::Undefined(_)) => false,
-      (AvmValue::Object(_), AvmValue::Boolean(_)) => unimplemented!("Object == Boolean"),
+      (AvmValue::Object(l), r @ AvmValue::Boolean(_)) => self.equals_primitive(r, l)?,
       (AvmValue::Object(_), AvmValue::Null(_)) => false,
-      (AvmValue::Object(_), AvmValue::Number(_)) => unimplemented!("Object == Number"),
-      (AvmValue::Object(_), AvmValue::Object(_)) => unimplemented!("Object == Object"),
-      (AvmValue::Object(_), AvmValue::String(_)) => unimplemented!("Object == String"),
+      (AvmValue::Object(l), r @ AvmValue::Number(_)) => self.equals_primitive(r, l)?,
+      (AvmValue::Object(l), AvmValue::Object(r)) => l.ptr_eq(&r),
+      (AvmValue::Object(l), r @ AvmValue::String(_)) => self.equals_primitive(r, l)?,
       (AvmValue::Object(_), AvmValue::Undefined(_)) => false,
       (AvmValue::String(l), AvmValue::Boolean(r)) => l.to_avm_number().value() == r.to_avm_number().value(),
       (AvmValue::String(_), AvmValue::Null(_)) => false,
       (AvmValue::String(l), AvmValue::Number(r)) => l.to_avm_number().value() == r.value(),
-      (AvmValue::String(_), AvmValue::Object(_)) => unimplemented!("String == Object"),
+      (l @ AvmValue::String(_), AvmValue::Object(r)) => self.equals_primitive(l, r)?,
       (AvmValue::String(l), AvmValue::String(r)) => l.value() == r.value(),
       (AvmValue::String(_), AvmValue::Undefined(_)) => false,
       (AvmValue::Undefined(_), AvmValue::Boolean(_)) => false,
@@ -562,28 +1377,115 @@ impl<'ectx, 'gc: 'ectx> ExecutionContext<'ectx, 'gc> {
       (AvmValue::Undefined(_), AvmValue::String(_)) => false,
       (AvmValue::Undefined(_), AvmValue::Undefined(_)) => true,
     };
+    Ok(result)
+  }
 
-    self.frame.stack.push(AvmValue::boolean(result));
+  /// Compares the primitive `value` with the result of `ToPrimitive(obj)` (ECMA 262-3, section 11.9.3, steps 20-21).
+  ///
+  /// Booleans are compared as numbers, so they can be compared with the primitive value directly.
+  fn equals_primitive(&mut self, value: AvmValue<'gc>, obj: AvmObjectRef<'gc>) -> Result<bool, AvmValue<'gc>> {
+    let primitive = obj.to_avm_primitive(&mut self.as_function_context(), ToPrimitiveHint::Default)?;
+    self.abstract_equals(value, AvmValue::from(primitive))
+  }
+
+  /// Makes the constructor below the top of the stack inherit from the constructor at the top of
+  /// the stack (`class Sub extends Base`).
+  ///
+  /// `Sub.prototype` is replaced by a new object inheriting from `Base.prototype`, with
+  /// `constructor` and `__constructor__` set to `Base`. Nothing happens if `Base` is not an object.
+  fn exec_extends(&mut self) -> () {
+    let superclass = self.frame.stack.pop();
+    let subclass = self.frame.stack.pop();
+    if !matches!(superclass, AvmValue::Object(_)) {
+      return;
+    }
+    let super_prototype = match self.get_member(&superclass, "prototype") {
+      Ok(Some(AvmValue::Object(super_prototype))) => Some(super_prototype),
+      Ok(_) => None,
+      Err(exception) => return self.throw(exception),
+    };
+    let prototype = AvmObject::new(self.vm.gc, super_prototype).unwrap();
+    {
+      let mut prototype = prototype.0.borrow_mut();
+      prototype.define(String::from("constructor"), superclass.clone(), DONT_ENUM);
+      prototype.define(String::from("__constructor__"), superclass, DONT_ENUM);
+    }
+    if let Err(exception) = self.vm.set_member(&subclass, String::from("prototype"), AvmValue::Object(prototype)) {
+      self.throw(exception);
+    }
+  }
+
+  /// Flash Lite `fscommand2`: the top of the stack holds the argument count (including the
+  /// command name), followed by the command name and its arguments.
+  fn exec_fs_command2(&mut self) -> () {
+    let arg_count = match self.pop_count() {
+      Ok(arg_count) => arg_count,
+      Err(exception) => return self.throw(exception),
+    };
+    let command = self.frame.stack.pop();
+    let command = match command.to_avm_string(&mut self.as_function_context()) {
+      Ok(command) => command,
+      Err(exception) => return self.throw(exception),
+    };
+    let mut args: Vec<String> = Vec::new();
+    for _ in 1..arg_count {
+      let arg = self.frame.stack.pop();
+      match arg.to_avm_string(&mut self.as_function_context()) {
+        Ok(arg) => args.push(arg.value().to_string()),
+        Err(exception) => return self.throw(exception),
+      }
+    }
+    self.vm.host.fs_command(command.value(), &args);
+    // The host hook does not report a status code
+    self.frame.stack.push(AvmValue::UNDEFINED);
+  }
+
+  fn get_member(&mut self, target: &AvmValue<'gc>, key: &str) -> Result<Option<AvmValue<'gc>>, AvmValue<'gc>> {
+    self.vm.get_member(target, key)
+  }
+
+  fn exec_get_time(&mut self) -> () {
+    let time = self.vm.host.get_time();
+    self.frame.stack.push(AvmValue::number(time));
+  }
+
+  fn exec_get_url(&mut self, action: &avm1::actions::GetUrl) -> () {
+    match get_fs_command(&action.url) {
+      Some(command) => self.vm.host.fs_command(command, ::std::slice::from_ref(&action.target)),
+      None => self.vm.host.get_url(&action.url, &action.target),
+    }
+  }
+
+  fn exec_get_url2(&mut self, _action: &avm1::actions::GetUrl2) -> () {
+    let target = self.frame.stack.pop();
+    let url = self.frame.stack.pop();
+    let target = match target.to_avm_string(&mut self.as_function_context()) {
+      Ok(target) => target,
+      Err(exception) => return self.throw(exception),
+    };
+    let url = match url.to_avm_string(&mut self.as_function_context()) {
+      Ok(url) => url,
+      Err(exception) => return self.throw(exception),
+    };
+    match get_fs_command(url.value()) {
+      Some(command) => self.vm.host.fs_command(command, &[target.value().to_string()]),
+      None => self.vm.host.get_url(url.value(), target.value()),
+    }
   }
 
   fn exec_get_member(&mut self) -> () {
     let key = self.frame.stack.pop();
     let target = self.frame.stack.pop();
 
-    let key: String = String::from(key.to_avm_string(&mut self.as_function_context()).unwrap().value());
-
-    let result = match target {
-      AvmValue::Null(_) => None,
-      AvmValue::Object(ref avm_object) => {
-        avm_object.0.borrow().get(&key)
-      }
-      AvmValue::Undefined(_) => None,
-      _ => unimplemented!("GetMember(boxable-primitive)"),
+    let key: String = match key.to_avm_string(&mut self.as_function_context()) {
+      Ok(key) => String::from(key.value()),
+      Err(exception) => return self.throw(exception),
     };
 
-    let result = match result {
-      Some(r) => r,
-      None => {
+    let result = match self.get_member(&target, &key) {
+      Ok(Some(r)) => r,
+      Err(exception) => return self.throw(exception),
+      Ok(None) => {
         let warning = Warning::TargetHasNoProperty(
           TargetHasNoProperty {
             target: String::from("foo"), // TODO: Do not hard-code target name
@@ -600,8 +1502,27 @@ impl<'ectx, 'gc: 'ectx> ExecutionContext<'ectx, 'gc> {
 
   fn exec_get_variable(&mut self) -> () {
     let name = self.frame.stack.pop();
-    let name = name.to_avm_string(&mut self.as_function_context()).unwrap();
-    let value = self.frame.scope.borrow().get(name.value());
+    let name = match name.to_avm_string(&mut self.as_function_context()) {
+      Ok(name) => name,
+      Err(exception) => return self.throw(exception),
+    };
+    let value = match name.value().rfind('.') {
+      Some(index) if index > 0 => {
+        let (path, key) = (&name.value()[..index], &name.value()[index + 1..]);
+        let target = match self.resolve_path(path) {
+          Ok(target) => target,
+          Err(exception) => return self.throw(exception),
+        };
+        match self.get_member(&target, key) {
+          Ok(value) => value,
+          Err(exception) => return self.throw(exception),
+        }
+      }
+      _ => match self.get_variable(name.value()) {
+        Ok(value) => value,
+        Err(exception) => return self.throw(exception),
+      },
+    };
     let value = match value {
       Some(v) => v,
       None => {
@@ -621,7 +1542,10 @@ impl<'ectx, 'gc: 'ectx> ExecutionContext<'ectx, 'gc> {
     let right = self.frame.stack.pop();
     let left = self.frame.stack.pop();
 
-    let result = self.abstract_compare(&right, &left).unwrap_or(false);
+    let result = match self.abstract_compare(&right, &left) {
+      Ok(result) => result.unwrap_or(false),
+      Err(exception) => return self.throw(exception),
+    };
 
     self.frame.stack.push(AvmValue::boolean(result));
   }
@@ -635,22 +1559,36 @@ impl<'ectx, 'gc: 'ectx> ExecutionContext<'ectx, 'gc> {
   }
 
   fn exec_increment(&mut self) -> () {
-    let arg = self.frame.stack.pop();
-    let arg = arg.to_avm_number().value();
+    let arg = match self.pop_number() {
+      Ok(arg) => arg,
+      Err(exception) => return self.throw(exception),
+    };
     let result = AvmValue::number(arg + 1f64);
     self.frame.stack.push(result)
   }
 
   fn exec_init_array(&mut self) -> () {
-    unimplemented!()
+    let item_count = match self.pop_count() {
+      Ok(item_count) => item_count,
+      Err(exception) => return self.throw(exception),
+    };
+    let items: Vec<AvmValue<'gc>> = (0..item_count).map(|_| self.frame.stack.pop()).collect();
+    let array = new_array(&mut self.as_function_context(), items);
+    self.frame.stack.push(AvmValue::Object(array))
   }
 
   fn exec_init_object(&mut self) -> () {
-    let property_count: usize = to_usize(self.frame.stack.pop()).unwrap();
+    let property_count = match self.pop_count() {
+      Ok(property_count) => property_count,
+      Err(exception) => return self.throw(exception),
+    };
     let obj: AvmObjectRef = AvmObject::new(self.vm.gc, Some(self.vm.realm.obj_p.clone())).unwrap();
     for _ in 0..property_count {
       let value: AvmValue = self.frame.stack.pop();
-      let key: String = String::from(self.frame.stack.pop().to_avm_string(&mut self.as_function_context()).unwrap().value());
+      let key: String = match self.frame.stack.pop().to_avm_string(&mut self.as_function_context()) {
+        Ok(key) => String::from(key.value()),
+        Err(exception) => return self.throw(exception),
+      };
       obj.0.borrow_mut().set(key, value);
     }
     self.frame.stack.push(AvmValue::Object(obj))
@@ -670,41 +1608,93 @@ impl<'ectx, 'gc: 'ectx> ExecutionContext<'ectx, 'gc> {
     let right = self.frame.stack.pop();
     let left = self.frame.stack.pop();
 
-    let result = self.abstract_compare(&left, &right).unwrap_or(false);
+    let result = match self.abstract_compare(&left, &right) {
+      Ok(result) => result.unwrap_or(false),
+      Err(exception) => return self.throw(exception),
+    };
 
     self.frame.stack.push(AvmValue::boolean(result));
   }
 
+  /// Computes the remainder of the division, the result has the sign of the dividend (`fmod`).
+  fn exec_modulo(&mut self) -> () {
+    let right = match self.pop_number() {
+      Ok(right) => right,
+      Err(exception) => return self.throw(exception),
+    };
+    let left = match self.pop_number() {
+      Ok(left) => left,
+      Err(exception) => return self.throw(exception),
+    };
+    self.frame.stack.push(AvmValue::number(left % right));
+  }
+
   fn exec_multiply(&mut self) -> () {
-    let right = self.frame.stack.pop().legacy_to_avm_number().value();
-    let left = self.frame.stack.pop().legacy_to_avm_number().value();
+    let right = match self.pop_number() {
+      Ok(right) => right,
+      Err(exception) => return self.throw(exception),
+    };
+    let left = match self.pop_number() {
+      Ok(left) => left,
+      Err(exception) => return self.throw(exception),
+    };
     self.frame.stack.push(AvmValue::Number(AvmNumber::new(left * right)));
   }
 
-  fn exec_new_object(&mut self) -> () {
-    let ctr_name = self.frame.stack.pop();
+  fn exec_new_method(&mut self) -> () {
+    let key = self.frame.stack.pop();
+    let target = self.frame.stack.pop();
     let arg_count = self.frame.stack.pop();
+    let args = match self.pop_args(arg_count) {
+      Ok(args) => args,
+      Err(exception) => return self.throw(exception),
+    };
 
-    let ctr_name = ctr_name.to_avm_string(&mut self.as_function_context()).unwrap();
-    let arg_count = arg_count.to_avm_number();
+    let ctr = match key {
+      AvmValue::Undefined(_) => target,
+      key => {
+        let key = match key.to_avm_string(&mut self.as_function_context()) {
+          Ok(key) => key,
+          Err(exception) => return self.throw(exception),
+        };
+        if key.value().is_empty() {
+          target
+        } else {
+          match self.get_member(&target, key.value()) {
+            Ok(ctr) => ctr.unwrap_or(AvmValue::UNDEFINED),
+            Err(exception) => return self.throw(exception),
+          }
+        }
+      }
+    };
 
-    if arg_count.value() != 0f64 {
-      unimplemented!("NewObject with non-zero arg count")
+    match self.vm.construct(ctr, &args) {
+      Ok(result) => self.frame.stack.push(result),
+      Err(exception) => self.throw(exception),
     }
+  }
 
-    let ctr = self.frame.scope.borrow().get(ctr_name.value()).unwrap_or(AvmValue::UNDEFINED);
-    let this_arg: AvmValue = AvmValue::Object(AvmObject::new(self.vm.gc, Some(self.vm.realm.obj_p.clone())).unwrap());
-
-    let result = self.apply(ctr, this_arg.clone(), &[]);
+  fn exec_new_object(&mut self) -> () {
+    let ctr_name = self.frame.stack.pop();
+    let arg_count = self.frame.stack.pop();
+    let args = match self.pop_args(arg_count) {
+      Ok(args) => args,
+      Err(exception) => return self.throw(exception),
+    };
 
-    match result {
-      Ok(_) => {
-        // Discard result
-      }
-      Err(_) => unimplemented!("Throw support"),
+    let ctr_name = match ctr_name.to_avm_string(&mut self.as_function_context()) {
+      Ok(ctr_name) => ctr_name,
+      Err(exception) => return self.throw(exception),
+    };
+    let ctr = match self.get_variable(ctr_name.value()) {
+      Ok(ctr) => ctr.unwrap_or(AvmValue::UNDEFINED),
+      Err(exception) => return self.throw(exception),
     };
 
-    self.frame.stack.push(this_arg);
+    match self.vm.construct(ctr, &args) {
+      Ok(result) => self.frame.stack.push(result),
+      Err(exception) => self.throw(exception),
+    }
   }
 
   fn exec_push_duplicate(&mut self) -> () {
@@ -749,11 +1739,140 @@ impl<'ectx, 'gc: 'ectx> ExecutionContext<'ectx, 'gc> {
     }
   }
 
+  /// Pushes a random integer in `[0, max)`, or `0` if `max` is not strictly positive.
+  fn exec_random_number(&mut self) -> () {
+    let max = match self.pop_number() {
+      Ok(max) => max.trunc(),
+      Err(exception) => return self.throw(exception),
+    };
+    let result = if max > 0f64 {
+      (self.vm.host.random() * max).floor()
+    } else {
+      0f64
+    };
+    self.frame.stack.push(AvmValue::number(result));
+  }
+
+  fn exec_remove_sprite(&mut self) -> () {
+    let target = self.frame.stack.pop();
+    let target = match target.to_avm_string(&mut self.as_function_context()) {
+      Ok(target) => target,
+      Err(exception) => return self.throw(exception),
+    };
+    self.vm.host.remove_sprite(target.value());
+  }
+
+  fn exec_return(&mut self) -> () {
+    self.frame.call_result = self.frame.stack.pop();
+    self.frame.ip = self.frame.code.len();
+  }
+
+  fn exec_set_member(&mut self) -> () {
+    let value = self.frame.stack.pop();
+    let key = self.frame.stack.pop();
+    let target = self.frame.stack.pop();
+    let key = match key.to_avm_string(&mut self.as_function_context()) {
+      Ok(key) => key,
+      Err(exception) => return self.throw(exception),
+    };
+    if let Err(exception) = self.vm.set_member(&target, key.value().to_string(), value) {
+      self.throw(exception);
+    }
+  }
+
   fn exec_set_variable(&mut self) -> () {
     let value = self.frame.stack.pop();
     let name = self.frame.stack.pop();
-    let name = name.to_avm_string(&mut self.as_function_context()).unwrap();
-    self.frame.scope.borrow_mut().set(name.value().to_owned(), value);
+    let name = match name.to_avm_string(&mut self.as_function_context()) {
+      Ok(name) => name,
+      Err(exception) => return self.throw(exception),
+    };
+    // Dotted paths (`obj.prop`) assign a member of the object designated by the prefix
+    match name.value().rfind('.') {
+      Some(index) if index > 0 => {
+        let (path, key) = (&name.value()[..index], &name.value()[index + 1..]);
+        let result = self.resolve_path(path).and_then(|target| self.vm.set_member(&target, key.to_owned(), value));
+        if let Err(exception) = result {
+          self.throw(exception);
+        }
+      }
+      _ => {
+        if let Err(exception) = self.set_variable(name.value().to_owned(), value) {
+          self.throw(exception);
+        }
+      }
+    }
+  }
+
+  /// Returns the value of the closest variable named `name`, or `None` if it does not exist.
+  fn get_variable(&mut self, name: &str) -> Result<Option<AvmValue<'gc>>, AvmValue<'gc>> {
+    let scope = Gc::clone(&self.frame.scope);
+    self.vm.get_scope_variable(&scope, name)
+  }
+
+  /// Updates the closest variable named `name`, or creates it in the timeline scope.
+  fn set_variable(&mut self, name: String, value: AvmValue<'gc>) -> Result<(), AvmValue<'gc>> {
+    match Scope::find(&self.frame.scope, &name, self.vm.swf_version, self.vm.max_prototype_depth) {
+      Some(ScopeBinding::Variable(scope)) => scope.borrow_mut().set_local(name, value),
+      Some(ScopeBinding::Property(object)) => return self.vm.set_member(&AvmValue::Object(object), name, value),
+      None => self.frame.scope.borrow_mut().set_in_timeline(name, value),
+    }
+    Ok(())
+  }
+
+  /// Resolves a dotted path of variable and member names (`a.b.c`).
+  fn resolve_path(&mut self, path: &str) -> AvmResult<'gc> {
+    let mut parts = path.split('.');
+    let first = parts.next().unwrap_or("");
+    let mut value = self.get_variable(first)?.unwrap_or(AvmValue::UNDEFINED);
+    for part in parts {
+      value = self.get_member(&value, part)?.unwrap_or(AvmValue::UNDEFINED);
+    }
+    Ok(value)
+  }
+
+  fn exec_stack_swap(&mut self) -> () {
+    let top = self.frame.stack.pop();
+    let below = self.frame.stack.pop();
+    self.frame.stack.push(top);
+    self.frame.stack.push(below);
+  }
+
+  fn exec_start_drag(&mut self) -> () {
+    let target = self.frame.stack.pop();
+    let lock_center = self.frame.stack.pop().legacy_to_avm_number().value() != 0f64;
+    let has_constraint = self.frame.stack.pop().legacy_to_avm_number().value() != 0f64;
+    // The constraint rectangle is only on the stack if the flag is set
+    let constraint = if has_constraint {
+      let bottom = match self.pop_number() {
+        Ok(bottom) => bottom,
+        Err(exception) => return self.throw(exception),
+      };
+      let right = match self.pop_number() {
+        Ok(right) => right,
+        Err(exception) => return self.throw(exception),
+      };
+      let top = match self.pop_number() {
+        Ok(top) => top,
+        Err(exception) => return self.throw(exception),
+      };
+      let left = match self.pop_number() {
+        Ok(left) => left,
+        Err(exception) => return self.throw(exception),
+      };
+      Some(DragConstraint { left, top, right, bottom })
+    } else {
+      None
+    };
+    let target = match target.to_avm_string(&mut self.as_function_context()) {
+      Ok(target) => target,
+      Err(exception) => return self.throw(exception),
+    };
+    self.vm.host.start_drag(target.value(), lock_center, constraint);
+  }
+
+  fn exec_stop_sounds(&mut self) -> () {
+    self.vm.host.stop_sounds();
   }
 
   fn exec_store_register(&mut self, action: &avm1::actions::StoreRegister) -> () {
@@ -769,7 +1888,7 @@ impl<'ectx, 'gc: 'ectx> ExecutionContext<'ectx, 'gc> {
       (AvmValue::Boolean(l), AvmValue::Boolean(r)) => l.value() == r.value(),
       (AvmValue::Null(_), AvmValue::Null(_)) => true,
       (AvmValue::Number(l), AvmValue::Number(r)) => l.value() == r.value(),
-      (AvmValue::Object(_l), AvmValue::Object(_r)) => unimplemented!("StrictEquals(Object, Object)"),
+      (AvmValue::Object(l), AvmValue::Object(r)) => l.ptr_eq(&r),
       (AvmValue::String(l), AvmValue::String(r)) => l.value() == r.value(),
       (AvmValue::Undefined(_), AvmValue::Undefined(_)) => true,
       _ => false,
@@ -779,38 +1898,127 @@ impl<'ectx, 'gc: 'ectx> ExecutionContext<'ectx, 'gc> {
   }
 
   fn exec_string_add(&mut self) -> () {
-    let right = self.frame.stack.pop().to_avm_string(&mut self.as_function_context()).unwrap().value().to_string();
-    let left = self.frame.stack.pop().to_avm_string(&mut self.as_function_context()).unwrap().value().to_string();
+    let right = match self.pop_string() {
+      Ok(right) => right,
+      Err(exception) => return self.throw(exception),
+    };
+    let left = match self.pop_string() {
+      Ok(left) => left,
+      Err(exception) => return self.throw(exception),
+    };
     self.frame.stack.push(AvmValue::string(self.vm.gc, format!("{}{}", left, right)).unwrap());
   }
 
   fn exec_string_equals(&mut self) -> () {
-    let right = self.frame.stack.pop().to_avm_string(&mut self.as_function_context()).unwrap().value().to_string();
-    let left = self.frame.stack.pop().to_avm_string(&mut self.as_function_context()).unwrap().value().to_string();
+    let right = match self.pop_string() {
+      Ok(right) => right,
+      Err(exception) => return self.throw(exception),
+    };
+    let left = match self.pop_string() {
+      Ok(left) => left,
+      Err(exception) => return self.throw(exception),
+    };
     let result = left == right;
     self.frame.stack.push(AvmValue::legacy_boolean(result, self.vm.swf_version));
   }
 
   fn exec_string_length(&mut self) -> () {
-    let value = self.frame.stack.pop().to_avm_string(&mut self.as_function_context()).unwrap().value().to_string();
+    let value = match self.pop_string() {
+      Ok(value) => value,
+      Err(exception) => return self.throw(exception),
+    };
     // TODO: Checked conversion
     self.frame.stack.push(AvmValue::number(value.len() as f64));
   }
 
   fn exec_subtract(&mut self) -> () {
-    let right = self.frame.stack.pop().legacy_to_avm_number().value();
-    let left = self.frame.stack.pop().legacy_to_avm_number().value();
+    let right = match self.pop_number() {
+      Ok(right) => right,
+      Err(exception) => return self.throw(exception),
+    };
+    let left = match self.pop_number() {
+      Ok(left) => left,
+      Err(exception) => return self.throw(exception),
+    };
     self.frame.stack.push(AvmValue::number(left - right))
   }
 
+  /// Truncates the value to a signed 32-bit integer (`int(x)`).
+  fn exec_to_integer(&mut self) -> () {
+    let arg = self.frame.stack.pop();
+    let arg = match arg.to_avm_number_with_context(&mut self.as_function_context()) {
+      Ok(arg) => arg,
+      Err(exception) => return self.throw(exception),
+    };
+    self.frame.stack.push(AvmValue::number(f64::from(arg.to_int32())));
+  }
+
+  fn exec_to_number(&mut self) -> () {
+    let arg = self.frame.stack.pop();
+    let arg = match arg.to_avm_number_with_context(&mut self.as_function_context()) {
+      Ok(arg) => arg,
+      Err(exception) => return self.throw(exception),
+    };
+    self.frame.stack.push(AvmValue::Number(arg));
+  }
+
+  fn exec_to_string(&mut self) -> () {
+    let arg = self.frame.stack.pop();
+    let arg = match arg.to_avm_string(&mut self.as_function_context()) {
+      Ok(arg) => arg,
+      Err(exception) => return self.throw(exception),
+    };
+    self.frame.stack.push(AvmValue::String(arg));
+  }
+
+  fn exec_toggle_quality(&mut self) -> () {
+    self.vm.host.toggle_quality();
+  }
+
+  fn exec_throw(&mut self) -> () {
+    let exception = self.frame.stack.pop();
+    self.throw(exception);
+  }
+
   fn exec_trace(&mut self) -> () {
     // `undefined` is always `undefined` when passed to `trace`, even for swf_version < 7.
     match self.frame.stack.pop() {
       AvmValue::Undefined(_) => self.vm.host.trace("undefined"),
-      avm_value => self.vm.host.trace(avm_value.to_avm_string(&mut self.as_function_context()).unwrap().value()),
+      avm_value => match avm_value.to_avm_string(&mut self.as_function_context()) {
+        Ok(value) => self.vm.host.trace(value.value()),
+        Err(exception) => self.throw(exception),
+      },
     };
   }
 
+  /// Registers the blocks of a `Try` action, the try block starts after the action.
+  fn exec_try(&mut self, action: &avm1::actions::Try) -> () {
+    let start = self.frame.ip;
+    let catch_start = start + usize::from(action.try_size);
+    let finally_start = catch_start + usize::from(action.catch_size.unwrap_or(0));
+    let end = finally_start + usize::from(action.finally_size.unwrap_or(0));
+    let catch_target = match (action.catch_size, &action.catch_target) {
+      (None, _) => None,
+      (Some(_), &avm1::actions::r#try::CatchTarget::Register(register)) => Some(CatchTarget::Register(register)),
+      (Some(_), &avm1::actions::r#try::CatchTarget::Variable(ref name)) => Some(CatchTarget::Variable(name.clone())),
+    };
+    self.frame.try_blocks.push(TryBlock {
+      start,
+      catch_start,
+      finally_start,
+      end,
+      catch_target,
+      state: TryState::Try,
+      pending: None,
+    });
+  }
+
+  fn exec_type_of(&mut self) -> () {
+    let arg = self.frame.stack.pop();
+    let result = AvmValue::string(self.vm.gc, String::from(arg.type_of())).unwrap();
+    self.frame.stack.push(result);
+  }
+
   fn add_to_ip(&mut self, offset: i16) -> () {
     // static I16_MIN_SUCCESSOR: i16 = std::i16::MIN + 1; // -0x7fff
     let new_ip: usize = match offset {
@@ -822,9 +2030,11 @@ impl<'ectx, 'gc: 'ectx> ExecutionContext<'ectx, 'gc> {
   }
 
   // Implementation of the abstract relational comparison algorithm from ECMA 262-3, section 11.8.5
-  fn abstract_compare(&mut self, left: &AvmValue<'gc>, right: &AvmValue<'gc>) -> Option<bool> {
-    let left = left.to_avm_primitive(&mut self.as_function_context(), ToPrimitiveHint::Number).unwrap();
-    let right = right.to_avm_primitive(&mut self.as_function_context(), ToPrimitiveHint::Number).unwrap();
+  //
+  // Returns `None` if a value is `NaN` (the result is `undefined`).
+  fn abstract_compare(&mut self, left: &AvmValue<'gc>, right: &AvmValue<'gc>) -> Result<Option<bool>, AvmValue<'gc>> {
+    let left = left.to_avm_primitive(&mut self.as_function_context(), ToPrimitiveHint::Number)?;
+    let right = right.to_avm_primitive(&mut self.as_function_context(), ToPrimitiveHint::Number)?;
 
     match (left, right) {
       (AvmPrimitive::String(_l), AvmPrimitive::String(_r)) => {
@@ -834,61 +2044,59 @@ impl<'ectx, 'gc: 'ectx> ExecutionContext<'ectx, 'gc> {
         let left = left.to_avm_number().value();
         let right = right.to_avm_number().value();
         if left.is_nan() || right.is_nan() {
-          None
+          Ok(None)
         } else {
-          Some(left < right)
+          Ok(Some(left < right))
         }
       }
     }
   }
 
   pub fn apply(&mut self, callable: AvmValue<'gc>, this_arg: AvmValue<'gc>, args: &[AvmValue<'gc>]) -> AvmResult<'gc> {
-    if !args.is_empty() {
-      unimplemented!("`apply` with non-empty `args`");
-    }
-
-    let obj = match callable {
-      AvmValue::Object(obj) => obj,
-      _ => return Err(AvmValue::string(self.vm.gc, String::from("TypeError: `callable` is not an object")).unwrap()),
-    };
-    let (code, scope) = match obj.0.borrow().callable {
-      Some(AvmCallable::AvmFunction(ref c)) => (c.code.clone(), Gc::clone(&c.scope)),
-      Some(AvmCallable::HostFunction(ref c)) => unimplemented!("Apply(HostFunction)"),
-      None => return Err(AvmValue::string(self.vm.gc, String::from("TypeError: `callable` is an object with an empty `callable` property")).unwrap()),
-    };
+    self.vm.apply(callable, this_arg, args)
+  }
 
-    let scope = Scope::child(scope);
-    let scope = self.vm.gc.alloc(GcRefCell::new(scope)).unwrap();
+  /// Pops a value and converts it to a number with the rules of the current SWF version.
+  fn pop_number(&mut self) -> Result<f64, AvmValue<'gc>> {
+    let value = self.frame.stack.pop();
+    Ok(value.to_avm_number_versioned(&mut self.as_function_context())?.value())
+  }
 
-    let frame: CallFrame = CallFrame {
-      code: &code,
-      ip: 0,
-      this: this_arg,
-      call_result: AvmValue::UNDEFINED,
-      stack: Stack::new(),
-      registers: RegisterTable::new(4),
-      scope,
-      parent: Some(&self.frame),
-    };
+  /// Pops a value and converts it to a string.
+  fn pop_string(&mut self) -> Result<String, AvmValue<'gc>> {
+    let value = self.frame.stack.pop();
+    Ok(value.to_avm_string(&mut self.as_function_context())?.value().to_string())
+  }
 
-    let mut ectx = ExecutionContext::new(self.vm, frame);
+  /// Pops an item count (for arrays, objects or `fscommand2` arguments).
+  fn pop_count(&mut self) -> Result<usize, AvmValue<'gc>> {
+    let count = self.frame.stack.pop();
+    self.item_count(count)
+  }
 
-    const MAX_ACTIONS: usize = 1000;
-    for _ in 0..MAX_ACTIONS {
-      let has_advanced = ectx.next();
-      if !has_advanced {
-        break;
-      }
-    }
+  /// Converts an item count popped from the stack.
+  ///
+  /// Invalid counts are `0`, and the count is clamped to the stack depth: the byte code cannot make
+  /// the VM read or allocate past the values actually pushed.
+  fn item_count(&mut self, count: AvmValue<'gc>) -> Result<usize, AvmValue<'gc>> {
+    let count = count.to_avm_number_versioned(&mut self.as_function_context())?.value();
+    let count: usize = if count > 0f64 { count as usize } else { 0 };
+    Ok(count.min(self.frame.stack.len()))
+  }
 
-    Ok(ectx.frame.call_result.clone())
+  /// Pops the arguments of a call with the argument count `arg_count` (the first argument is at the
+  /// top of the stack).
+  fn pop_args(&mut self, arg_count: AvmValue<'gc>) -> Result<Vec<AvmValue<'gc>>, AvmValue<'gc>> {
+    let arg_count = self.item_count(arg_count)?;
+    Ok((0..arg_count).map(|_| self.frame.stack.pop()).collect())
   }
 
-  pub(crate) fn as_function_context(&mut self) -> ContextImpl<'gc> {
+  pub(crate) fn as_function_context(&mut self) -> ContextImpl<'_, 'gc> {
     ContextImpl {
-      gc: self.vm.gc,
-      _swf_version: self.vm.swf_version,
-      _this: self.frame.this.clone(),
+      vm: self.vm,
+      this: self.frame.this.clone(),
+      args: Vec::new(),
+      call_type: CallType::Apply,
     }
   }
 }
@@ -1,7 +1,11 @@
 use scoped_gc::{Gc, GcAllocErr, GcScope};
 
+use crate::avm1::Vm;
+use crate::host::Host;
+use crate::realm::Realm;
+use crate::timer::Timer;
 use crate::values::{AvmString, AvmValue};
-use crate::values::object::AvmCallable;
+use crate::values::object::AvmObjectRef;
 
 // Ok: normal return
 // Err: throw value
@@ -13,53 +17,117 @@ pub trait Context<'gc> {
   fn string(&mut self, s: String) -> Result<Gc<'gc, AvmString>, GcAllocErr>;
 
   fn swf_version(&self) -> u8;
+
+  /// Maximum number of prototypes visited by property lookups.
+  fn max_prototype_depth(&self) -> usize;
+
+  fn gc(&self) -> &'gc GcScope<'gc>;
+
+  /// Returns the builtin objects of the VM.
+  fn realm(&self) -> &Realm<'gc>;
+
+  /// Returns the host running the VM.
+  fn host(&self) -> &'gc dyn Host;
+
+  /// Associates a library symbol with a class (`Object.registerClass`).
+  fn register_class(&mut self, name: String, class: Option<AvmObjectRef<'gc>>) -> ();
+
+  /// Registers a timer (`setInterval`, `setTimeout`), returns its id.
+  fn add_timer(&mut self, timer: Timer<'gc>) -> i32;
+
+  /// Removes a timer (`clearInterval`), returns `true` if it was active.
+  fn remove_timer(&mut self, id: i32) -> bool;
+}
+
+/// How a function was invoked.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CallType {
+  /// Regular call
+  Apply,
+  /// `new` operator, `this` is the object being created
+  Construct,
 }
 
 pub trait CallContext<'gc>: Context<'gc> {
+  /// Returns whether the current function was called as a function or as a constructor.
+  fn call_type(&self) -> CallType;
+
   /// Returns the current `this` value.
   // TODO: Allow only `Object` and `Undefined` as `this` values
   fn this(&mut self) -> AvmValue<'gc>;
+
+  /// Returns the arguments passed to the current function.
+  fn args(&self) -> &[AvmValue<'gc>];
+
+  /// Returns the argument at `index`, or `undefined` if it was not supplied.
+  fn arg(&self, index: usize) -> AvmValue<'gc> {
+    self.args().get(index).cloned().unwrap_or(AvmValue::UNDEFINED)
+  }
 }
 
 // Struct passed to native functions to handle context-sensitive operations
-pub(crate) struct ContextImpl<'gc> {
-  pub(crate) gc: &'gc GcScope<'gc>,
-  pub(crate) _swf_version: u8,
-  pub(crate) _this: AvmValue<'gc>,
+pub(crate) struct ContextImpl<'a, 'gc: 'a> {
+  pub(crate) vm: &'a mut Vm<'gc>,
+  pub(crate) this: AvmValue<'gc>,
+  pub(crate) args: Vec<AvmValue<'gc>>,
+  pub(crate) call_type: CallType,
 }
 
-impl<'gc> Context<'gc> for ContextImpl<'gc> {
-  fn apply(&mut self, callable: AvmValue<'gc>, this_arg: AvmValue<'gc>, _args: &[AvmValue<'gc>]) -> AvmResult<'gc> {
-    match callable {
-      AvmValue::Object(obj) => {
-        match &obj.0.borrow().callable {
-          Some(AvmCallable::HostFunction(f)) => {
-            let mut sub_ctx = ContextImpl {
-              gc: self.gc,
-              _swf_version: self._swf_version,
-              _this: this_arg,
-            };
-
-            (f.func)(&mut sub_ctx)
-          },
-          _ => unimplemented!("Apply(non-HostFunction)")
-        }
-      },
-      _ => unimplemented!("Apply(non-Object)")
-    }
+impl<'a, 'gc: 'a> Context<'gc> for ContextImpl<'a, 'gc> {
+  fn apply(&mut self, callable: AvmValue<'gc>, this_arg: AvmValue<'gc>, args: &[AvmValue<'gc>]) -> AvmResult<'gc> {
+    self.vm.apply(callable, this_arg, args)
   }
 
   fn string(&mut self, s: String) -> Result<Gc<'gc, AvmString>, GcAllocErr> {
-    AvmString::new(self.gc, s)
+    AvmString::new(self.vm.gc, s)
   }
 
   fn swf_version(&self) -> u8 {
-    self._swf_version
+    self.vm.swf_version
+  }
+
+  fn max_prototype_depth(&self) -> usize {
+    self.vm.max_prototype_depth
+  }
+
+  fn gc(&self) -> &'gc GcScope<'gc> {
+    self.vm.gc
+  }
+
+  fn realm(&self) -> &Realm<'gc> {
+    &self.vm.realm
+  }
+
+  fn host(&self) -> &'gc dyn Host {
+    self.vm.host
+  }
+
+  fn register_class(&mut self, name: String, class: Option<AvmObjectRef<'gc>>) -> () {
+    match class {
+      Some(class) => self.vm.registered_classes.insert(name, class),
+      None => self.vm.registered_classes.remove(&name),
+    };
+  }
+
+  fn add_timer(&mut self, timer: Timer<'gc>) -> i32 {
+    self.vm.timers.add(timer)
+  }
+
+  fn remove_timer(&mut self, id: i32) -> bool {
+    self.vm.timers.remove(id)
   }
 }
 
-impl<'gc> CallContext<'gc> for ContextImpl<'gc> {
+impl<'a, 'gc: 'a> CallContext<'gc> for ContextImpl<'a, 'gc> {
+  fn call_type(&self) -> CallType {
+    self.call_type
+  }
+
   fn this(&mut self) -> AvmValue<'gc> {
-    self._this.clone()
+    self.this.clone()
+  }
+
+  fn args(&self) -> &[AvmValue<'gc>] {
+    &self.args
   }
 }
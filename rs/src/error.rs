@@ -21,18 +21,58 @@ impl std::fmt::Display for TargetHasNoProperty {
   }
 }
 
+/// The action data could not be parsed (truncated or malformed action record).
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct CorruptDataWarning;
+
+impl std::fmt::Display for CorruptDataWarning {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+    write!(f, "Warning: Failed to parse corrupt data.")
+  }
+}
+
+/// The action code is not supported: the action was skipped.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct UnknownActionWarning {
+  pub code: u8,
+}
+
+impl std::fmt::Display for UnknownActionWarning {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+    write!(f, "Warning: Skipped unknown action 0x{:02x}.", self.code)
+  }
+}
+
+/// An exception was not caught by any `Try` action of the script.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct UncaughtExceptionWarning {
+  /// The exception, converted to a string
+  pub value: String,
+}
+
+impl std::fmt::Display for UncaughtExceptionWarning {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+    write!(f, "Warning: Uncaught exception: {}", self.value)
+  }
+}
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub enum Warning {
+  CorruptData(CorruptDataWarning),
   ReferenceToUndeclaredVariable(ReferenceToUndeclaredVariableWarning),
   TargetHasNoProperty(TargetHasNoProperty),
+  UncaughtException(UncaughtExceptionWarning),
+  UnknownAction(UnknownActionWarning),
 }
 
 impl std::fmt::Display for Warning {
   fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
     match self {
+      &Warning::CorruptData(ref w) => w.fmt(f),
       &Warning::ReferenceToUndeclaredVariable(ref w) => w.fmt(f),
       &Warning::TargetHasNoProperty(ref w) => w.fmt(f),
+      &Warning::UncaughtException(ref w) => w.fmt(f),
+      &Warning::UnknownAction(ref w) => w.fmt(f),
     }
   }
 }
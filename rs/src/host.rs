@@ -1,17 +1,156 @@
-use ::std::cell::RefCell;
+use ::std::cell::{Cell, RefCell};
+use ::std::time::{Instant, SystemTime, UNIX_EPOCH};
 
+use std::collections::HashMap;
+
+use crate::avm1::TargetId;
 use crate::error::Warning;
 
 pub trait Host {
   fn trace(&self, message: &str) -> ();
 
   fn warn(&self, warning: &Warning) -> ();
+
+  /// Returns the number of milliseconds elapsed since the player started.
+  ///
+  /// The default clock is stopped at `0`.
+  fn get_time(&self) -> f64 {
+    0f64
+  }
+
+  /// Returns the current date, in milliseconds since the Unix epoch (used by `Date`).
+  ///
+  /// The default date is the epoch.
+  fn get_date(&self) -> f64 {
+    0f64
+  }
+
+  /// Returns the offset of the local timezone in minutes, as `Date.prototype.getTimezoneOffset`.
+  ///
+  /// The offset is positive west of UTC: `-60` for UTC+01:00. The default timezone is UTC.
+  fn get_timezone_offset(&self) -> f64 {
+    0f64
+  }
+
+  /// Returns a random number in `[0, 1)`.
+  ///
+  /// There is no default: each host owns its generator, so its sequence only depends on its own
+  /// seed (see `XorShift64`).
+  fn random(&self) -> f64;
+
+  /// Returns the player version, as reported by `getVersion()` (`"<platform> <major>,<minor>,<build>,<revision>"`).
+  fn version(&self) -> String {
+    String::from("LNX 32,0,0,0")
+  }
+
+  /// Forwards a command to the container (`getURL("FSCommand:...")` or Flash Lite's `FsCommand2`).
+  ///
+  /// `command` is the name following the `FSCommand:` prefix.
+  fn fs_command(&self, _command: &str, _args: &[String]) -> () {}
+
+  /// Opens `url` in the window or frame `target` (`getURL` for URLs without the `FSCommand:` prefix).
+  fn get_url(&self, _url: &str, _target: &str) -> () {}
+
+  /// Duplicates the sprite at the path `target` as a new sprite `name`, placed at `depth`.
+  fn clone_sprite(&self, _target: &str, _name: &str, _depth: i32) -> () {}
+
+  /// Removes the sprite at the path `target`.
+  fn remove_sprite(&self, _target: &str) -> () {}
+
+  /// Starts dragging the sprite at the path `target`.
+  ///
+  /// If `lock_center` is `true`, the sprite is centered on the mouse pointer.
+  fn start_drag(&self, _target: &str, _lock_center: bool, _constraint: Option<DragConstraint>) -> () {}
+
+  /// Stops the current drag operation, if any.
+  fn stop_drag(&self) -> () {}
+
+  /// Toggles the display quality between high and low.
+  fn toggle_quality(&self) -> () {}
+
+  /// Stops all the sounds currently playing.
+  fn stop_sounds(&self) -> () {}
+
+  /// Returns the actions of a frame, for the `Call` action.
+  ///
+  /// `frame` is a frame label or number, optionally prefixed by a target path (`/a/b:label`).
+  /// Relative paths are resolved from `target`.
+  fn get_frame_script(&self, _target: Option<TargetId>, _frame: &str) -> Option<FrameScript> {
+    None
+  }
+
+  /// Loads the content at `url`, for `XML.prototype.load` and `sendAndLoad`.
+  ///
+  /// `data` is the body sent with the request, if any. Returns `None` if the content is unavailable.
+  fn load_url(&self, _url: &str, _data: Option<&str>) -> Option<String> {
+    None
+  }
 }
 
-pub struct NativeHost;
+/// Actions of a frame, resolved by the host.
+#[derive(Debug, Clone)]
+pub struct FrameScript {
+  /// Target owning the frame: the script runs on its timeline.
+  pub target: Option<TargetId>,
+
+  /// AVM1 byte code of the frame actions.
+  pub code: Vec<u8>,
+}
+
+/// Rectangle (in pixels, relative to the parent of the dragged sprite) constraining a drag operation.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DragConstraint {
+  pub left: f64,
+  pub top: f64,
+  pub right: f64,
+  pub bottom: f64,
+}
+
+/// Seeded pseudo-random number generator (xorshift64*).
+///
+/// It is not suitable for cryptographic use, but it is fast and fully determined by its seed.
+pub struct XorShift64(Cell<u64>);
+
+impl XorShift64 {
+  pub fn new(seed: u64) -> XorShift64 {
+    let rng = XorShift64(Cell::new(0));
+    rng.set_seed(seed);
+    rng
+  }
+
+  pub fn set_seed(&self, seed: u64) -> () {
+    // The state must never be zero
+    self.0.set(if seed == 0 { 0x9e37_79b9_7f4a_7c15 } else { seed });
+  }
+
+  /// Returns the next random number in `[0, 1)`.
+  pub fn next_f64(&self) -> f64 {
+    let mut x = self.0.get();
+    x ^= x >> 12;
+    x ^= x << 25;
+    x ^= x >> 27;
+    self.0.set(x);
+    let bits = x.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 11;
+    (bits as f64) / ((1u64 << 53) as f64)
+  }
+}
+
+pub struct NativeHost {
+  start: Instant,
+  rng: XorShift64,
+}
 
 impl NativeHost {
-  pub fn new() -> NativeHost { NativeHost }
+  pub fn new() -> NativeHost {
+    let seed = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map(|d| d.as_nanos() as u64)
+      .unwrap_or(0);
+    NativeHost {
+      start: Instant::now(),
+      rng: XorShift64::new(seed),
+    }
+  }
 }
 
 impl Host for NativeHost {
@@ -22,6 +161,26 @@ impl Host for NativeHost {
   fn warn(&self, warning: &Warning) -> () {
     eprintln!("{}", warning.to_string());
   }
+
+  fn get_time(&self) -> f64 {
+    self.start.elapsed().as_millis() as f64
+  }
+
+  fn get_date(&self) -> f64 {
+    SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map(|d| d.as_millis() as f64)
+      .unwrap_or(0f64)
+  }
+
+  // The standard library does not expose the local timezone
+  fn get_timezone_offset(&self) -> f64 {
+    0f64
+  }
+
+  fn random(&self) -> f64 {
+    self.rng.next_f64()
+  }
 }
 
 pub struct NoOpHost;
@@ -30,18 +189,67 @@ impl Host for NoOpHost {
   fn trace(&self, _message: &str) -> () {}
 
   fn warn(&self, _warning: &Warning) -> () {}
+
+  fn random(&self) -> f64 {
+    0f64
+  }
 }
 
+/// Default seed of the `LoggedHost` random number generator, so runs are replayable.
+pub const LOGGED_HOST_SEED: u64 = 0x6176_6d6f_7265;
+
 pub struct LoggedHost {
   pub logs: RefCell<Vec<String>>,
+  time: Cell<f64>,
+  date: Cell<f64>,
+  timezone_offset: Cell<f64>,
+  rng: XorShift64,
+  frame_scripts: RefCell<HashMap<String, FrameScript>>,
+  url_contents: RefCell<HashMap<String, String>>,
 }
 
 impl LoggedHost {
   pub fn new() -> LoggedHost {
     LoggedHost {
       logs: RefCell::new(Vec::new()),
+      time: Cell::new(0f64),
+      date: Cell::new(0f64),
+      timezone_offset: Cell::new(0f64),
+      rng: XorShift64::new(LOGGED_HOST_SEED),
+      frame_scripts: RefCell::new(HashMap::new()),
+      url_contents: RefCell::new(HashMap::new()),
     }
   }
+
+  /// Registers the script returned when a `Call` action requests `frame` (matched verbatim).
+  pub fn set_frame_script(&self, frame: &str, script: FrameScript) -> () {
+    self.frame_scripts.borrow_mut().insert(frame.to_string(), script);
+  }
+
+  /// Registers the content returned when `url` is loaded (matched verbatim).
+  pub fn set_url_content(&self, url: &str, content: &str) -> () {
+    self.url_contents.borrow_mut().insert(url.to_string(), content.to_string());
+  }
+
+  /// Sets the value returned by `get_time` (the clock does not advance on its own).
+  pub fn set_time(&self, time: f64) -> () {
+    self.time.set(time);
+  }
+
+  /// Sets the value returned by `get_date`, in milliseconds since the Unix epoch.
+  pub fn set_date(&self, date: f64) -> () {
+    self.date.set(date);
+  }
+
+  /// Sets the timezone offset in minutes (positive west of UTC).
+  pub fn set_timezone_offset(&self, offset: f64) -> () {
+    self.timezone_offset.set(offset);
+  }
+
+  /// Restarts the random number sequence from `seed`.
+  pub fn set_random_seed(&self, seed: u64) -> () {
+    self.rng.set_seed(seed);
+  }
 }
 
 impl Host for LoggedHost {
@@ -52,4 +260,69 @@ impl Host for LoggedHost {
   fn warn(&self, warning: &Warning) -> () {
     self.logs.borrow_mut().push(warning.to_string());
   }
+
+  fn get_time(&self) -> f64 {
+    self.time.get()
+  }
+
+  fn get_date(&self) -> f64 {
+    self.date.get()
+  }
+
+  fn get_timezone_offset(&self) -> f64 {
+    self.timezone_offset.get()
+  }
+
+  fn random(&self) -> f64 {
+    self.rng.next_f64()
+  }
+
+  fn fs_command(&self, command: &str, args: &[String]) -> () {
+    self.logs.borrow_mut().push(format!("FSCommand: {}({})", command, args.join(", ")));
+  }
+
+  fn get_url(&self, url: &str, target: &str) -> () {
+    self.logs.borrow_mut().push(format!("getURL({}, {})", url, target));
+  }
+
+  fn clone_sprite(&self, target: &str, name: &str, depth: i32) -> () {
+    self.logs.borrow_mut().push(format!("cloneSprite({}, {}, {})", target, name, depth));
+  }
+
+  fn remove_sprite(&self, target: &str) -> () {
+    self.logs.borrow_mut().push(format!("removeSprite({})", target));
+  }
+
+  fn start_drag(&self, target: &str, lock_center: bool, constraint: Option<DragConstraint>) -> () {
+    let message = match constraint {
+      Some(c) => format!("startDrag({}, {}, {}, {}, {}, {})", target, lock_center, c.left, c.top, c.right, c.bottom),
+      None => format!("startDrag({}, {})", target, lock_center),
+    };
+    self.logs.borrow_mut().push(message);
+  }
+
+  fn stop_drag(&self) -> () {
+    self.logs.borrow_mut().push(String::from("stopDrag()"));
+  }
+
+  fn toggle_quality(&self) -> () {
+    self.logs.borrow_mut().push(String::from("toggleQuality()"));
+  }
+
+  fn stop_sounds(&self) -> () {
+    self.logs.borrow_mut().push(String::from("stopSounds()"));
+  }
+
+  fn get_frame_script(&self, _target: Option<TargetId>, frame: &str) -> Option<FrameScript> {
+    self.frame_scripts.borrow().get(frame).cloned()
+  }
+
+  fn load_url(&self, url: &str, data: Option<&str>) -> Option<String> {
+    let message = match data {
+      Some(data) => format!("load({}, {})", url, data),
+      None => format!("load({})", url),
+    };
+    self.logs.borrow_mut().push(message);
+    self.url_contents.borrow().get(url).cloned()
+  }
 }
@@ -8,6 +8,7 @@ pub mod context;
 pub mod error;
 pub mod host;
 pub mod realm;
+pub mod timer;
 pub mod values;
 
 #[cfg(test)]
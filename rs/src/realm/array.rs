@@ -0,0 +1,608 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use scoped_gc::{GcRefCell, GcScope};
+
+use crate::context::{AvmResult, CallContext, Context};
+use crate::realm::{define_methods, link_constructor, new_host_function, NativeFunction};
+use crate::values::{AvmObject, AvmValue};
+use crate::values::object::{array_index, AvmObjectClass, AvmObjectPrototype, AvmObjectRef, DONT_DELETE, DONT_ENUM, READ_ONLY};
+use crate::values::property_map::PropertyMap;
+
+// > 15.4 Array Objects
+// >
+// > Every Array object has a length property whose value is always a nonnegative integer less than 2^32.
+// > The value of the length property is numerically greater than the name of every property whose name is an
+// > array index; whenever a property of an Array object is created or changed, other properties are adjusted as
+// > necessary to maintain this invariant.
+//
+// The invariant itself is maintained by `AvmObject::set`.
+
+/// Options of `Array.prototype.sort` and `Array.prototype.sortOn`
+const CASE_INSENSITIVE: i32 = 1;
+const DESCENDING: i32 = 2;
+const UNIQUE_SORT: i32 = 4;
+const RETURN_INDEXED_ARRAY: i32 = 8;
+const NUMERIC: i32 = 16;
+
+pub struct ArrayRealm<'gc> {
+  /// `Array`
+  pub array: AvmObjectRef<'gc>,
+
+  /// `Array.prototype`
+  pub array_p: AvmObjectRef<'gc>,
+}
+
+impl<'gc> ArrayRealm<'gc> {
+  pub fn new(gc: &'gc GcScope<'gc>, obj_p: &AvmObjectRef<'gc>, func_p: &AvmObjectRef<'gc>) -> Self {
+    let array_p = AvmObject::new(gc, Some(obj_p.clone())).unwrap();
+    let methods: [(&str, NativeFunction<'gc>); 12] = [
+      ("concat", concat),
+      ("join", join),
+      ("pop", pop),
+      ("push", push),
+      ("reverse", reverse),
+      ("shift", shift),
+      ("slice", slice),
+      ("sort", sort),
+      ("sortOn", sort_on),
+      ("splice", splice),
+      ("toString", to_string),
+      ("unshift", unshift),
+    ];
+    define_methods(gc, func_p, &array_p, &methods);
+
+    let array = new_host_function(gc, func_p, array);
+    let constants: [(&str, i32); 5] = [
+      ("CASEINSENSITIVE", CASE_INSENSITIVE),
+      ("DESCENDING", DESCENDING),
+      ("UNIQUESORT", UNIQUE_SORT),
+      ("RETURNINDEXEDARRAY", RETURN_INDEXED_ARRAY),
+      ("NUMERIC", NUMERIC),
+    ];
+    for (name, value) in constants.iter() {
+      let value = AvmValue::number(f64::from(*value));
+      array.0.borrow_mut().define(String::from(*name), value, DONT_ENUM | DONT_DELETE | READ_ONLY);
+    }
+    link_constructor(&array, &array_p);
+
+    ArrayRealm { array, array_p }
+  }
+}
+
+/// Creates a new Array object containing `items`.
+pub(crate) fn new_array<'gc, C: Context<'gc> + ?Sized>(ctx: &mut C, items: Vec<AvmValue<'gc>>) -> AvmObjectRef<'gc> {
+  let array = AvmObject {
+    class: AvmObjectClass::Array,
+    prototype: AvmObjectPrototype::Object(ctx.realm().array.array_p.clone()),
+    properties: PropertyMap::new(),
+    callable: None,
+    watchers: HashMap::new(),
+    value: None,
+  };
+  let array = ctx.gc().alloc(GcRefCell::new(array)).map(AvmObjectRef).unwrap();
+  write_items(&array, items);
+  array
+}
+
+/// Returns the `length` of the array-like object `obj`, as an array index bound.
+pub(crate) fn read_length<'gc>(obj: &AvmObjectRef<'gc>) -> u32 {
+  let length: f64 = match obj.0.borrow().get("length") {
+    None | Some(AvmValue::Object(_)) => 0f64,
+    Some(v) => v.to_avm_number().value(),
+  };
+  if length.is_finite() && length > 0f64 {
+    length.min(f64::from(u32::MAX - 1)) as u32
+  } else {
+    0
+  }
+}
+
+/// Returns the own items of the array-like object `obj` with an index below `length`, sorted by
+/// index.
+///
+/// Holes are skipped: the cost depends on the number of properties, not on `length`.
+pub(crate) fn read_sparse_items<'gc>(obj: &AvmObjectRef<'gc>, length: u32) -> Vec<(u32, AvmValue<'gc>)> {
+  let mut items: Vec<(u32, AvmValue<'gc>)> = obj.0.borrow().properties
+    .iter()
+    .filter_map(|(key, property)| match array_index(key) {
+      Some(index) if index < length => Some((index, property.value.clone())),
+      _ => None,
+    })
+    .collect();
+  items.sort_by_key(|&(index, _)| index);
+  items
+}
+
+/// Reads the array-like object `obj` into a vector, holes are read as `undefined`.
+///
+/// The vector stops at the last item: trailing holes are dropped, so a large `length` does not
+/// allocate anything.
+pub(crate) fn read_items<'gc>(obj: &AvmObjectRef<'gc>) -> Vec<AvmValue<'gc>> {
+  let sparse = read_sparse_items(obj, read_length(obj));
+  let mut items: Vec<AvmValue<'gc>> = Vec::with_capacity(sparse.len());
+  for (index, value) in sparse {
+    items.resize(index as usize, AvmValue::UNDEFINED);
+    items.push(value);
+  }
+  items
+}
+
+/// Replaces the items of the array-like object `obj` by the sorted `items` and sets its `length`.
+///
+/// Only the existing items and the new ones are visited, whatever the length.
+pub(crate) fn write_sparse_items<'gc>(obj: &AvmObjectRef<'gc>, length: u32, items: Vec<(u32, AvmValue<'gc>)>) -> () {
+  let old_length = read_length(obj);
+  let mut obj = obj.0.borrow_mut();
+  obj.properties.retain(|key, property| match array_index(key) {
+    Some(index) => index >= old_length || !property.deletable,
+    None => true,
+  });
+  for (index, value) in items {
+    obj.set(index.to_string(), value);
+  }
+  obj.set(String::from("length"), AvmValue::number(f64::from(length)));
+}
+
+/// Replaces the items of the array-like object `obj`.
+pub(crate) fn write_items<'gc>(obj: &AvmObjectRef<'gc>, items: Vec<AvmValue<'gc>>) -> () {
+  let length = items.len() as u32;
+  write_sparse_items(obj, length, items.into_iter().enumerate().map(|(i, item)| (i as u32, item)).collect());
+}
+
+/// Inserts `value` at `index` in the array-like object `obj`, moving the following items.
+///
+/// Appending (`index` equal to the length) does not visit the existing items.
+pub(crate) fn insert_item<'gc>(obj: &AvmObjectRef<'gc>, index: u32, value: AvmValue<'gc>) -> () {
+  let length = read_length(obj);
+  let mut obj = obj.0.borrow_mut();
+  for i in (index..length).rev() {
+    match obj.get_local(&i.to_string()) {
+      Some(item) => obj.set((i + 1).to_string(), item),
+      None => { obj.delete(&(i + 1).to_string()); }
+    }
+  }
+  obj.set(index.to_string(), value);
+  obj.set(String::from("length"), AvmValue::number(f64::from(length.max(index) + 1)));
+}
+
+/// Removes the item at `index` from the array-like object `obj`, moving the following items.
+pub(crate) fn remove_item<'gc>(obj: &AvmObjectRef<'gc>, index: u32) -> () {
+  let length = read_length(obj);
+  if index >= length {
+    return;
+  }
+  let mut obj = obj.0.borrow_mut();
+  for i in (index + 1)..length {
+    match obj.get_local(&i.to_string()) {
+      Some(item) => obj.set((i - 1).to_string(), item),
+      None => { obj.delete(&(i - 1).to_string()); }
+    }
+  }
+  obj.delete(&(length - 1).to_string());
+  obj.set(String::from("length"), AvmValue::number(f64::from(length - 1)));
+}
+
+/// Creates a new Array object with the provided `length` and sorted `items`.
+fn new_sparse_array<'gc, C: Context<'gc> + ?Sized>(ctx: &mut C, length: u32, items: Vec<(u32, AvmValue<'gc>)>) -> AvmObjectRef<'gc> {
+  let array = new_array(ctx, Vec::new());
+  write_sparse_items(&array, length, items);
+  array
+}
+
+/// Moves the sorted `items` at or after `start` by `offset`, for `shift`, `unshift` and `splice`.
+///
+/// The items before `start` are kept, the items moved out of the index range are dropped.
+fn shift_items<'gc>(items: Vec<(u32, AvmValue<'gc>)>, start: u32, offset: i64) -> Vec<(u32, AvmValue<'gc>)> {
+  items
+    .into_iter()
+    .filter_map(|(index, value)| {
+      if index < start {
+        return Some((index, value));
+      }
+      let index = i64::from(index) + offset;
+      if 0 <= index && index < i64::from(u32::MAX - 1) {
+        Some((index as u32, value))
+      } else {
+        None
+      }
+    })
+    .collect()
+}
+
+fn this_object<'gc>(ctx: &mut dyn CallContext<'gc>) -> Option<AvmObjectRef<'gc>> {
+  match ctx.this() {
+    AvmValue::Object(obj) => Some(obj),
+    _ => None,
+  }
+}
+
+/// Returns `Some(obj)` if `value` is an Array object.
+fn as_array<'gc>(value: &AvmValue<'gc>) -> Option<AvmObjectRef<'gc>> {
+  match value {
+    AvmValue::Object(ref obj) if obj.0.borrow().class == AvmObjectClass::Array => Some(obj.clone()),
+    _ => None,
+  }
+}
+
+/// Converts an argument to an integer, `NaN` becomes `0`.
+fn to_integer<'gc>(ctx: &mut dyn CallContext<'gc>, value: &AvmValue<'gc>) -> Result<f64, AvmValue<'gc>> {
+  let value = value.to_avm_number_with_context(ctx)?.value();
+  Ok(if value.is_nan() { 0f64 } else { value.trunc() })
+}
+
+/// Resolves a possibly negative index relative to the end of an array of length `len`.
+fn relative_index(index: f64, len: usize) -> usize {
+  if index < 0f64 {
+    (len as f64 + index).max(0f64) as usize
+  } else {
+    index.min(len as f64) as usize
+  }
+}
+
+fn array<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let args = ctx.args().to_vec();
+  let result = match args.as_slice() {
+    [AvmValue::Number(length)] => {
+      let result = new_array(ctx, Vec::new());
+      result.0.borrow_mut().set(String::from("length"), AvmValue::Number(*length));
+      result
+    }
+    _ => new_array(ctx, args),
+  };
+  Ok(AvmValue::Object(result))
+}
+
+fn concat<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let (mut length, mut items): (u32, Vec<(u32, AvmValue<'gc>)>) = match this_object(ctx) {
+    Some(obj) => {
+      let length = read_length(&obj);
+      (length, read_sparse_items(&obj, length))
+    }
+    None => (0, Vec::new()),
+  };
+  for arg in ctx.args().to_vec() {
+    match as_array(&arg) {
+      Some(arg) => {
+        let arg_length = read_length(&arg);
+        let offset = length;
+        items.extend(read_sparse_items(&arg, arg_length).into_iter().map(|(i, item)| (offset.saturating_add(i), item)));
+        length = length.saturating_add(arg_length);
+      }
+      None => {
+        items.push((length, arg));
+        length = length.saturating_add(1);
+      }
+    }
+  }
+  Ok(AvmValue::Object(new_sparse_array(ctx, length, items)))
+}
+
+fn join<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let separator: String = match ctx.arg(0) {
+    AvmValue::Undefined(_) => String::from(","),
+    v => v.to_avm_string(ctx)?.value().to_string(),
+  };
+  join_items(ctx, &separator)
+}
+
+fn join_items<'gc>(ctx: &mut dyn CallContext<'gc>, separator: &str) -> AvmResult<'gc> {
+  let obj = match this_object(ctx) {
+    Some(obj) => obj,
+    None => return Ok(AvmValue::UNDEFINED),
+  };
+  let length = read_length(&obj);
+  let mut items = read_sparse_items(&obj, length).into_iter().peekable();
+  let mut result = String::new();
+  for i in 0..length {
+    if i > 0 {
+      result.push_str(separator);
+    }
+    let item = match items.peek() {
+      Some(&(index, _)) if index == i => items.next().unwrap().1,
+      _ => AvmValue::UNDEFINED,
+    };
+    result.push_str(item.to_avm_string(ctx)?.value());
+  }
+  Ok(AvmValue::string(ctx.gc(), result).unwrap())
+}
+
+fn pop<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let obj = match this_object(ctx) {
+    Some(obj) => obj,
+    None => return Ok(AvmValue::UNDEFINED),
+  };
+  let length = read_length(&obj);
+  let mut obj = obj.0.borrow_mut();
+  if length == 0 {
+    obj.set(String::from("length"), AvmValue::ZERO);
+    return Ok(AvmValue::UNDEFINED);
+  }
+  let key = (length - 1).to_string();
+  let result = obj.get_local(&key).unwrap_or(AvmValue::UNDEFINED);
+  obj.delete(&key);
+  obj.set(String::from("length"), AvmValue::number(f64::from(length - 1)));
+  Ok(result)
+}
+
+fn push<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let obj = match this_object(ctx) {
+    Some(obj) => obj,
+    None => return Ok(AvmValue::UNDEFINED),
+  };
+  let length = u64::from(read_length(&obj));
+  let args = ctx.args().to_vec();
+  let new_length = length + args.len() as u64;
+  let mut obj = obj.0.borrow_mut();
+  for (i, arg) in args.into_iter().enumerate() {
+    obj.set((length + i as u64).to_string(), arg);
+  }
+  obj.set(String::from("length"), AvmValue::number(new_length as f64));
+  Ok(AvmValue::number(new_length as f64))
+}
+
+fn reverse<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let obj = match this_object(ctx) {
+    Some(obj) => obj,
+    None => return Ok(AvmValue::UNDEFINED),
+  };
+  let length = read_length(&obj);
+  let items: Vec<(u32, AvmValue<'gc>)> = read_sparse_items(&obj, length)
+    .into_iter()
+    .rev()
+    .map(|(i, item)| (length - 1 - i, item))
+    .collect();
+  write_sparse_items(&obj, length, items);
+  Ok(AvmValue::Object(obj))
+}
+
+fn shift<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let obj = match this_object(ctx) {
+    Some(obj) => obj,
+    None => return Ok(AvmValue::UNDEFINED),
+  };
+  let length = read_length(&obj);
+  if length == 0 {
+    return Ok(AvmValue::UNDEFINED);
+  }
+  let mut items = read_sparse_items(&obj, length);
+  let result = match items.first() {
+    Some(&(0, _)) => items.remove(0).1,
+    _ => AvmValue::UNDEFINED,
+  };
+  write_sparse_items(&obj, length - 1, shift_items(items, 0, -1));
+  Ok(result)
+}
+
+fn slice<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let obj = match this_object(ctx) {
+    Some(obj) => obj,
+    None => return Ok(AvmValue::UNDEFINED),
+  };
+  let len = read_length(&obj) as usize;
+  let start = ctx.arg(0);
+  let start = relative_index(to_integer(ctx, &start)?, len);
+  let end = match ctx.arg(1) {
+    AvmValue::Undefined(_) => len,
+    end => relative_index(to_integer(ctx, &end)?, len),
+  };
+  if start >= end {
+    return Ok(AvmValue::Object(new_array(ctx, Vec::new())));
+  }
+  let items: Vec<(u32, AvmValue<'gc>)> = read_sparse_items(&obj, end as u32)
+    .into_iter()
+    .filter(|&(i, _)| i as usize >= start)
+    .map(|(i, item)| (i - start as u32, item))
+    .collect();
+  Ok(AvmValue::Object(new_sparse_array(ctx, (end - start) as u32, items)))
+}
+
+fn splice<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let obj = match this_object(ctx) {
+    Some(obj) => obj,
+    None => return Ok(AvmValue::UNDEFINED),
+  };
+  if ctx.args().is_empty() {
+    return Ok(AvmValue::UNDEFINED);
+  }
+  let len = read_length(&obj) as usize;
+  let start = ctx.arg(0);
+  let start = relative_index(to_integer(ctx, &start)?, len);
+  let delete_count: usize = match ctx.args().get(1).cloned() {
+    None => len - start,
+    Some(count) => to_integer(ctx, &count)?.max(0f64).min((len - start) as f64) as usize,
+  };
+  let end = start + delete_count;
+  let inserted: Vec<AvmValue<'gc>> = ctx.args().iter().skip(2).cloned().collect();
+
+  let (removed, kept): (Vec<_>, Vec<_>) = read_sparse_items(&obj, len as u32)
+    .into_iter()
+    .partition(|&(i, _)| start <= i as usize && (i as usize) < end);
+  let removed: Vec<(u32, AvmValue<'gc>)> = removed.into_iter().map(|(i, item)| (i - start as u32, item)).collect();
+  let offset = inserted.len() as i64 - delete_count as i64;
+  let mut items = shift_items(kept, end as u32, offset);
+  items.extend(inserted.into_iter().enumerate().map(|(i, item)| ((start + i) as u32, item)));
+  items.sort_by_key(|&(i, _)| i);
+  let new_length = (len as i64 + offset).min(i64::from(u32::MAX - 1)) as u32;
+  write_sparse_items(&obj, new_length, items);
+  Ok(AvmValue::Object(new_sparse_array(ctx, delete_count as u32, removed)))
+}
+
+fn to_string<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  join_items(ctx, ",")
+}
+
+fn unshift<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let obj = match this_object(ctx) {
+    Some(obj) => obj,
+    None => return Ok(AvmValue::UNDEFINED),
+  };
+  let length = read_length(&obj);
+  let args = ctx.args().to_vec();
+  let count = args.len() as u32;
+  let mut items: Vec<(u32, AvmValue<'gc>)> = args.into_iter().enumerate().map(|(i, item)| (i as u32, item)).collect();
+  items.extend(shift_items(read_sparse_items(&obj, length), 0, i64::from(count)));
+  let new_length = length.saturating_add(count).min(u32::MAX - 1);
+  write_sparse_items(&obj, new_length, items);
+  Ok(AvmValue::number(f64::from(new_length)))
+}
+
+fn is_callable(value: &AvmValue) -> bool {
+  match value {
+    AvmValue::Object(ref obj) => obj.0.borrow().callable.is_some(),
+    _ => false,
+  }
+}
+
+fn sort<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  // `sort(compareFunction, options)` or `sort(options)`
+  let (compare_fn, options) = if is_callable(&ctx.arg(0)) {
+    (Some(ctx.arg(0)), ctx.arg(1))
+  } else {
+    (None, ctx.arg(0))
+  };
+  let options: i32 = options.to_avm_number_with_context(ctx)?.to_int32();
+
+  sort_items(ctx, options, &mut |ctx, left, right| {
+    let ordering = match compare_fn {
+      Some(ref compare_fn) => {
+        let result = ctx.apply(compare_fn.clone(), AvmValue::UNDEFINED, &[left.clone(), right.clone()])?;
+        let result = result.to_avm_number_with_context(ctx)?.value();
+        if result < 0f64 {
+          Ordering::Less
+        } else if result > 0f64 {
+          Ordering::Greater
+        } else {
+          Ordering::Equal
+        }
+      }
+      None => compare_default(ctx, left, right, options)?,
+    };
+    Ok(if options & DESCENDING != 0 { ordering.reverse() } else { ordering })
+  })
+}
+
+fn sort_on<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  // `sortOn(fieldName, options)`, both arguments also accept arrays
+  let field_names: Vec<AvmValue<'gc>> = match as_array(&ctx.arg(0)) {
+    Some(names) => read_items(&names),
+    None => vec![ctx.arg(0)],
+  };
+  let mut fields: Vec<String> = Vec::with_capacity(field_names.len());
+  for name in field_names {
+    fields.push(name.to_avm_string(ctx)?.value().to_string());
+  }
+  let field_options: Vec<AvmValue<'gc>> = match as_array(&ctx.arg(1)) {
+    Some(options) => read_items(&options),
+    None => vec![ctx.arg(1); fields.len()],
+  };
+  let mut options: Vec<i32> = Vec::with_capacity(fields.len());
+  for i in 0..fields.len() {
+    let field_option = field_options.get(i).cloned().unwrap_or(AvmValue::UNDEFINED);
+    options.push(field_option.to_avm_number_with_context(ctx)?.to_int32());
+  }
+  // The flags changing the result are read from the first field
+  let global_options = options.first().cloned().unwrap_or(0);
+
+  sort_items(ctx, global_options, &mut |ctx, left, right| {
+    for (field, options) in fields.iter().zip(options.iter()) {
+      let left = get_field(left, field);
+      let right = get_field(right, field);
+      let ordering = compare_default(ctx, &left, &right, *options)?;
+      let ordering = if options & DESCENDING != 0 { ordering.reverse() } else { ordering };
+      if ordering != Ordering::Equal {
+        return Ok(ordering);
+      }
+    }
+    Ok(Ordering::Equal)
+  })
+}
+
+fn get_field<'gc>(value: &AvmValue<'gc>, field: &str) -> AvmValue<'gc> {
+  match value {
+    AvmValue::Object(ref obj) => obj.0.borrow().get(field).unwrap_or(AvmValue::UNDEFINED),
+    _ => AvmValue::UNDEFINED,
+  }
+}
+
+/// Compares two values as strings, or as numbers if the `NUMERIC` option is set.
+fn compare_default<'gc>(ctx: &mut dyn CallContext<'gc>, left: &AvmValue<'gc>, right: &AvmValue<'gc>, options: i32) -> Result<Ordering, AvmValue<'gc>> {
+  if options & NUMERIC != 0 {
+    let left = left.to_avm_number_with_context(ctx)?.value();
+    let right = right.to_avm_number_with_context(ctx)?.value();
+    return Ok(left.partial_cmp(&right).unwrap_or(Ordering::Equal));
+  }
+  let left = left.to_avm_string(ctx)?.value().to_string();
+  let right = right.to_avm_string(ctx)?.value().to_string();
+  if options & CASE_INSENSITIVE != 0 {
+    Ok(left.to_lowercase().cmp(&right.to_lowercase()))
+  } else {
+    Ok(left.cmp(&right))
+  }
+}
+
+type Comparator<'c, 'gc> =
+  dyn FnMut(&mut dyn CallContext<'gc>, &AvmValue<'gc>, &AvmValue<'gc>) -> Result<Ordering, AvmValue<'gc>> + 'c;
+
+/// Sorts the items of `this` and applies the `UNIQUESORT` and `RETURNINDEXEDARRAY` options.
+fn sort_items<'gc>(ctx: &mut dyn CallContext<'gc>, options: i32, compare: &mut Comparator<'_, 'gc>) -> AvmResult<'gc> {
+  let obj = match this_object(ctx) {
+    Some(obj) => obj,
+    None => return Ok(AvmValue::UNDEFINED),
+  };
+  let length = read_length(&obj);
+  let (positions, items): (Vec<u32>, Vec<AvmValue<'gc>>) = read_sparse_items(&obj, length).into_iter().unzip();
+  let indexes: Vec<usize> = (0..items.len()).collect();
+  let indexes = merge_sort(indexes, &mut |left, right| compare(ctx, &items[left], &items[right]))?;
+
+  if options & UNIQUE_SORT != 0 {
+    for pair in indexes.windows(2) {
+      if compare(ctx, &items[pair[0]], &items[pair[1]])? == Ordering::Equal {
+        return Ok(AvmValue::ZERO);
+      }
+    }
+  }
+
+  if options & RETURN_INDEXED_ARRAY != 0 {
+    let indexes: Vec<AvmValue<'gc>> = indexes.into_iter().map(|i| AvmValue::number(f64::from(positions[i]))).collect();
+    return Ok(AvmValue::Object(new_array(ctx, indexes)));
+  }
+
+  // Holes are moved after the items
+  let sorted: Vec<(u32, AvmValue<'gc>)> = indexes.into_iter().enumerate().map(|(i, index)| (i as u32, items[index].clone())).collect();
+  write_sparse_items(&obj, length, sorted);
+  Ok(AvmValue::Object(obj))
+}
+
+/// Stable merge sort supporting comparators that throw or are inconsistent.
+fn merge_sort<'gc, F>(mut items: Vec<usize>, compare: &mut F) -> Result<Vec<usize>, AvmValue<'gc>>
+where
+  F: FnMut(usize, usize) -> Result<Ordering, AvmValue<'gc>>,
+{
+  if items.len() <= 1 {
+    return Ok(items);
+  }
+  let right = items.split_off(items.len() / 2);
+  let left = merge_sort(items, compare)?;
+  let right = merge_sort(right, compare)?;
+
+  let mut result: Vec<usize> = Vec::with_capacity(left.len() + right.len());
+  let mut left = left.into_iter().peekable();
+  let mut right = right.into_iter().peekable();
+  loop {
+    let next = match (left.peek(), right.peek()) {
+      (Some(&l), Some(&r)) => {
+        if compare(r, l)? == Ordering::Less {
+          right.next()
+        } else {
+          left.next()
+        }
+      }
+      (Some(_), None) => left.next(),
+      (None, Some(_)) => right.next(),
+      (None, None) => break,
+    };
+    result.push(next.unwrap());
+  }
+  Ok(result)
+}
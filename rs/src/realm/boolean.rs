@@ -0,0 +1,73 @@
+use scoped_gc::GcScope;
+
+use crate::context::{AvmResult, CallContext, CallType};
+use crate::realm::{define_methods, link_constructor, new_host_function, NativeFunction};
+use crate::values::{AvmObject, AvmValue};
+use crate::values::object::{AvmObjectClass, AvmObjectRef};
+
+// > 15.6 Boolean Objects
+
+pub struct BooleanRealm<'gc> {
+  /// `Boolean`
+  pub boolean: AvmObjectRef<'gc>,
+
+  /// `Boolean.prototype`
+  pub boolean_p: AvmObjectRef<'gc>,
+}
+
+impl<'gc> BooleanRealm<'gc> {
+  pub fn new(gc: &'gc GcScope<'gc>, obj_p: &AvmObjectRef<'gc>, func_p: &AvmObjectRef<'gc>) -> Self {
+    let boolean_p = AvmObject::new(gc, Some(obj_p.clone())).unwrap();
+    let methods: [(&str, NativeFunction<'gc>); 2] = [("toString", to_string), ("valueOf", value_of)];
+    define_methods(gc, func_p, &boolean_p, &methods);
+
+    let boolean = new_host_function(gc, func_p, boolean);
+    link_constructor(&boolean, &boolean_p);
+
+    BooleanRealm { boolean, boolean_p }
+  }
+}
+
+/// `Boolean(value)` converts `value` to a boolean, `new Boolean(value)` creates a Boolean object.
+fn boolean<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let value = AvmValue::Boolean(ctx.arg(0).to_avm_boolean());
+  if ctx.call_type() == CallType::Apply {
+    return Ok(value);
+  }
+  let this = match ctx.this() {
+    AvmValue::Object(this) => this,
+    _ => return Ok(value),
+  };
+  {
+    let mut this = this.0.borrow_mut();
+    this.class = AvmObjectClass::Boolean;
+    this.value = Some(value);
+  }
+  Ok(AvmValue::Object(this))
+}
+
+/// Returns the boolean value of `this`, a primitive boolean or a Boolean object.
+fn this_boolean<'gc>(ctx: &mut dyn CallContext<'gc>) -> Option<bool> {
+  match ctx.this() {
+    AvmValue::Boolean(value) => Some(value.value()),
+    AvmValue::Object(ref this) if this.0.borrow().class == AvmObjectClass::Boolean => match this.0.borrow().value {
+      Some(AvmValue::Boolean(value)) => Some(value.value()),
+      _ => None,
+    },
+    _ => None,
+  }
+}
+
+/// `Boolean.prototype.toString()`
+fn to_string<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let result = match this_boolean(ctx) {
+    Some(value) => if value { "true" } else { "false" },
+    None => return Ok(AvmValue::UNDEFINED),
+  };
+  Ok(AvmValue::String(ctx.string(String::from(result)).unwrap()))
+}
+
+/// `Boolean.prototype.valueOf()`
+fn value_of<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  Ok(this_boolean(ctx).map_or(AvmValue::UNDEFINED, AvmValue::boolean))
+}
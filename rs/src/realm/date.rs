@@ -0,0 +1,501 @@
+use scoped_gc::GcScope;
+
+use crate::context::{AvmResult, CallContext, CallType};
+use crate::realm::{define_methods, link_constructor, new_host_function, NativeFunction};
+use crate::values::{AvmObject, AvmValue};
+use crate::values::object::{AvmObjectClass, AvmObjectRef};
+
+// > 15.9 Date Objects
+//
+// A Date object holds a time value: the number of milliseconds since 1970-01-01T00:00:00Z, or
+// `NaN` for invalid dates. Local time uses the fixed offset reported by the host.
+
+const MS_PER_SECOND: f64 = 1000f64;
+const MS_PER_MINUTE: f64 = 60_000f64;
+const MS_PER_HOUR: f64 = 3_600_000f64;
+const MS_PER_DAY: f64 = 86_400_000f64;
+
+const DAY_NAMES: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+const MONTH_NAMES: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+pub struct DateRealm<'gc> {
+  /// `Date`
+  pub date: AvmObjectRef<'gc>,
+
+  /// `Date.prototype`
+  pub date_p: AvmObjectRef<'gc>,
+}
+
+impl<'gc> DateRealm<'gc> {
+  pub fn new(gc: &'gc GcScope<'gc>, obj_p: &AvmObjectRef<'gc>, func_p: &AvmObjectRef<'gc>) -> Self {
+    let date_p = AvmObject::new(gc, Some(obj_p.clone())).unwrap();
+    let methods: [(&str, NativeFunction<'gc>); 37] = [
+      ("getDate", get_date),
+      ("getDay", get_day),
+      ("getFullYear", get_full_year),
+      ("getHours", get_hours),
+      ("getMilliseconds", get_milliseconds),
+      ("getMinutes", get_minutes),
+      ("getMonth", get_month),
+      ("getSeconds", get_seconds),
+      ("getTime", value_of),
+      ("getTimezoneOffset", get_timezone_offset),
+      ("getUTCDate", get_utc_date),
+      ("getUTCDay", get_utc_day),
+      ("getUTCFullYear", get_utc_full_year),
+      ("getUTCHours", get_utc_hours),
+      ("getUTCMilliseconds", get_utc_milliseconds),
+      ("getUTCMinutes", get_utc_minutes),
+      ("getUTCMonth", get_utc_month),
+      ("getUTCSeconds", get_utc_seconds),
+      ("getYear", get_year),
+      ("setDate", set_date),
+      ("setFullYear", set_full_year),
+      ("setHours", set_hours),
+      ("setMilliseconds", set_milliseconds),
+      ("setMinutes", set_minutes),
+      ("setMonth", set_month),
+      ("setSeconds", set_seconds),
+      ("setTime", set_time),
+      ("setUTCDate", set_utc_date),
+      ("setUTCFullYear", set_utc_full_year),
+      ("setUTCHours", set_utc_hours),
+      ("setUTCMilliseconds", set_utc_milliseconds),
+      ("setUTCMinutes", set_utc_minutes),
+      ("setUTCMonth", set_utc_month),
+      ("setUTCSeconds", set_utc_seconds),
+      ("setYear", set_year),
+      ("toString", to_string),
+      ("valueOf", value_of),
+    ];
+    define_methods(gc, func_p, &date_p, &methods);
+
+    let date = new_host_function(gc, func_p, date);
+    let date_methods: [(&str, NativeFunction<'gc>); 1] = [("UTC", utc)];
+    define_methods(gc, func_p, &date, &date_methods);
+    link_constructor(&date, &date_p);
+
+    DateRealm { date, date_p }
+  }
+}
+
+/// Components of a time value, in the order used by `Date.UTC` and the setters.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Field {
+  FullYear = 0,
+  Month = 1,
+  Date = 2,
+  Hours = 3,
+  Minutes = 4,
+  Seconds = 5,
+  Milliseconds = 6,
+}
+
+/// Returns the number of days since 1970-01-01 of a date of the proleptic Gregorian calendar.
+///
+/// `month` is in `[1, 12]`.
+fn days_from_civil(year: f64, month: f64, day: f64) -> f64 {
+  let year = if month <= 2f64 { year - 1f64 } else { year };
+  let era = (year / 400f64).floor();
+  let year_of_era = year - era * 400f64;
+  let month_index = if month > 2f64 { month - 3f64 } else { month + 9f64 };
+  let day_of_year = ((153f64 * month_index + 2f64) / 5f64).floor() + day - 1f64;
+  let day_of_era = year_of_era * 365f64 + (year_of_era / 4f64).floor() - (year_of_era / 100f64).floor() + day_of_year;
+  era * 146_097f64 + day_of_era - 719_468f64
+}
+
+/// Inverse of `days_from_civil`: returns `(year, month, day)`, with `month` in `[1, 12]`.
+fn civil_from_days(days: f64) -> (f64, f64, f64) {
+  let days = days + 719_468f64;
+  let era = (days / 146_097f64).floor();
+  let day_of_era = days - era * 146_097f64;
+  let year_of_era =
+    ((day_of_era - (day_of_era / 1460f64).floor() + (day_of_era / 36_524f64).floor() - (day_of_era / 146_096f64).floor())
+      / 365f64)
+      .floor();
+  let day_of_year = day_of_era - (365f64 * year_of_era + (year_of_era / 4f64).floor() - (year_of_era / 100f64).floor());
+  let month_index = ((5f64 * day_of_year + 2f64) / 153f64).floor();
+  let day = day_of_year - ((153f64 * month_index + 2f64) / 5f64).floor() + 1f64;
+  let month = if month_index < 10f64 { month_index + 3f64 } else { month_index - 9f64 };
+  let year = year_of_era + era * 400f64 + if month <= 2f64 { 1f64 } else { 0f64 };
+  (year, month, day)
+}
+
+/// Splits a (finite) time value into its components, see `Field`.
+fn to_fields(time: f64) -> [f64; 7] {
+  let days = (time / MS_PER_DAY).floor();
+  let (year, month, day) = civil_from_days(days);
+  let ms_in_day = time - days * MS_PER_DAY;
+  [
+    year,
+    month - 1f64,
+    day,
+    (ms_in_day / MS_PER_HOUR).floor(),
+    (ms_in_day / MS_PER_MINUTE).floor() % 60f64,
+    (ms_in_day / MS_PER_SECOND).floor() % 60f64,
+    ms_in_day % MS_PER_SECOND,
+  ]
+}
+
+/// `MakeDate(MakeDay(year, month, date), MakeTime(hours, minutes, seconds, ms))`
+///
+/// The components may overflow: `month = 12` is January of the next year.
+fn from_fields(fields: &[f64; 7]) -> f64 {
+  if fields.iter().any(|f| !f.is_finite()) {
+    return f64::NAN;
+  }
+  let fields: Vec<f64> = fields.iter().map(|f| f.trunc()).collect();
+  let year = fields[0] + (fields[1] / 12f64).floor();
+  let month = fields[1].rem_euclid(12f64);
+  let days = days_from_civil(year, month + 1f64, 1f64) + fields[2] - 1f64;
+  let time = fields[3] * MS_PER_HOUR + fields[4] * MS_PER_MINUTE + fields[5] * MS_PER_SECOND + fields[6];
+  days * MS_PER_DAY + time
+}
+
+/// `TimeClip(time)`
+fn time_clip(time: f64) -> f64 {
+  if !time.is_finite() || time.abs() > 8.64e15 {
+    f64::NAN
+  } else {
+    time.trunc() + 0f64
+  }
+}
+
+/// Returns the offset to add to a UTC time to get the local time.
+fn local_offset<'gc>(ctx: &mut dyn CallContext<'gc>) -> f64 {
+  -ctx.host().get_timezone_offset() * MS_PER_MINUTE
+}
+
+/// Reads the components passed to `Date.UTC` or the `Date` constructor.
+///
+/// Missing components default to the first day of the month at midnight, two-digit years are
+/// relative to 1900.
+fn read_fields<'gc>(ctx: &mut dyn CallContext<'gc>) -> Result<[f64; 7], AvmValue<'gc>> {
+  let mut fields: [f64; 7] = [0f64, 0f64, 1f64, 0f64, 0f64, 0f64, 0f64];
+  for (i, arg) in ctx.args().to_vec().into_iter().take(7).enumerate() {
+    fields[i] = arg.to_avm_number_with_context(ctx)?.value();
+  }
+  let year = fields[0].trunc();
+  if (0f64..=99f64).contains(&year) {
+    fields[0] = 1900f64 + year;
+  }
+  Ok(fields)
+}
+
+/// `Date()` returns the current date as a string.
+///
+/// `new Date()` uses the current date, `new Date(time)` a time value and
+/// `new Date(year, month, date, hours, minutes, seconds, ms)` local time components.
+fn date<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let now = ctx.host().get_date();
+  if ctx.call_type() == CallType::Apply {
+    let offset = local_offset(ctx);
+    let result = format_date(now, offset);
+    return Ok(AvmValue::String(ctx.string(result).unwrap()));
+  }
+  let time = match ctx.args().len() {
+    0 => now,
+    1 => ctx.arg(0).to_avm_number_with_context(ctx)?.value(),
+    _ => {
+      let fields = read_fields(ctx)?;
+      from_fields(&fields) - local_offset(ctx)
+    }
+  };
+  let this = match ctx.this() {
+    AvmValue::Object(this) => this,
+    _ => return Ok(AvmValue::UNDEFINED),
+  };
+  {
+    let mut this = this.0.borrow_mut();
+    this.class = AvmObjectClass::Date;
+    this.value = Some(AvmValue::number(time_clip(time)));
+  }
+  Ok(AvmValue::Object(this))
+}
+
+/// `Date.UTC(year, month, date, hours, minutes, seconds, ms)`
+fn utc<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let fields = read_fields(ctx)?;
+  Ok(AvmValue::number(time_clip(from_fields(&fields))))
+}
+
+/// Returns the Date object `this`.
+fn this_date<'gc>(ctx: &mut dyn CallContext<'gc>) -> Option<AvmObjectRef<'gc>> {
+  match ctx.this() {
+    AvmValue::Object(this) => {
+      if this.0.borrow().class == AvmObjectClass::Date {
+        Some(this)
+      } else {
+        None
+      }
+    }
+    _ => None,
+  }
+}
+
+/// Returns the time value of `this`, `NaN` if it is not a Date object.
+fn this_time<'gc>(ctx: &mut dyn CallContext<'gc>) -> f64 {
+  match this_date(ctx) {
+    Some(this) => match this.0.borrow().value {
+      Some(AvmValue::Number(time)) => time.value(),
+      _ => f64::NAN,
+    },
+    None => f64::NAN,
+  }
+}
+
+/// Returns the time value of `this`, shifted to local time unless `utc` is set.
+fn this_time_in<'gc>(ctx: &mut dyn CallContext<'gc>, utc: bool) -> f64 {
+  let time = this_time(ctx);
+  if utc {
+    time
+  } else {
+    time + local_offset(ctx)
+  }
+}
+
+/// Returns a component of `this` (`Date.prototype.get*` and `Date.prototype.getUTC*`).
+fn get_field<'gc>(ctx: &mut dyn CallContext<'gc>, field: Field, utc: bool) -> AvmResult<'gc> {
+  let time = this_time_in(ctx, utc);
+  if time.is_nan() {
+    return Ok(AvmValue::number(f64::NAN));
+  }
+  Ok(AvmValue::number(to_fields(time)[field as usize]))
+}
+
+/// Replaces the components of `this` starting at `field` with the arguments, then returns the
+/// new time value (`Date.prototype.set*` and `Date.prototype.setUTC*`).
+///
+/// The date setters only accept date components: `setMonth(month, date)` but not the hours.
+fn set_fields<'gc>(ctx: &mut dyn CallContext<'gc>, field: Field, utc: bool) -> AvmResult<'gc> {
+  let this = match this_date(ctx) {
+    Some(this) => this,
+    None => return Ok(AvmValue::UNDEFINED),
+  };
+  let time = this_time_in(ctx, utc);
+  // An invalid date is reset to the epoch when its year is set
+  let time = if time.is_nan() && field == Field::FullYear { 0f64 } else { time };
+  let mut fields = to_fields(time);
+  let first = field as usize;
+  let last = if first <= Field::Date as usize { Field::Date as usize } else { Field::Milliseconds as usize };
+  let args = ctx.args().to_vec();
+  let count = args.len().max(1).min(last - first + 1);
+  for i in 0..count {
+    let arg = args.get(i).cloned().unwrap_or(AvmValue::UNDEFINED);
+    fields[first + i] = arg.to_avm_number_with_context(ctx)?.value();
+  }
+  let time = from_fields(&fields);
+  let time = time_clip(if utc { time } else { time - local_offset(ctx) });
+  this.0.borrow_mut().value = Some(AvmValue::number(time));
+  Ok(AvmValue::number(time))
+}
+
+fn get_date<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  get_field(ctx, Field::Date, false)
+}
+
+/// `Date.prototype.getDay()`, `0` is Sunday.
+fn get_day<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let time = this_time_in(ctx, false);
+  Ok(AvmValue::number(week_day(time)))
+}
+
+fn get_full_year<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  get_field(ctx, Field::FullYear, false)
+}
+
+fn get_hours<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  get_field(ctx, Field::Hours, false)
+}
+
+fn get_milliseconds<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  get_field(ctx, Field::Milliseconds, false)
+}
+
+fn get_minutes<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  get_field(ctx, Field::Minutes, false)
+}
+
+fn get_month<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  get_field(ctx, Field::Month, false)
+}
+
+fn get_seconds<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  get_field(ctx, Field::Seconds, false)
+}
+
+/// `Date.prototype.getTimezoneOffset()`, in minutes.
+fn get_timezone_offset<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  Ok(AvmValue::number(ctx.host().get_timezone_offset()))
+}
+
+fn get_utc_date<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  get_field(ctx, Field::Date, true)
+}
+
+fn get_utc_day<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let time = this_time_in(ctx, true);
+  Ok(AvmValue::number(week_day(time)))
+}
+
+fn get_utc_full_year<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  get_field(ctx, Field::FullYear, true)
+}
+
+fn get_utc_hours<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  get_field(ctx, Field::Hours, true)
+}
+
+fn get_utc_milliseconds<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  get_field(ctx, Field::Milliseconds, true)
+}
+
+fn get_utc_minutes<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  get_field(ctx, Field::Minutes, true)
+}
+
+fn get_utc_month<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  get_field(ctx, Field::Month, true)
+}
+
+fn get_utc_seconds<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  get_field(ctx, Field::Seconds, true)
+}
+
+/// `Date.prototype.getYear()`, the local year minus 1900.
+fn get_year<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let time = this_time_in(ctx, false);
+  if time.is_nan() {
+    return Ok(AvmValue::number(f64::NAN));
+  }
+  Ok(AvmValue::number(to_fields(time)[Field::FullYear as usize] - 1900f64))
+}
+
+fn set_date<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  set_fields(ctx, Field::Date, false)
+}
+
+fn set_full_year<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  set_fields(ctx, Field::FullYear, false)
+}
+
+fn set_hours<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  set_fields(ctx, Field::Hours, false)
+}
+
+fn set_milliseconds<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  set_fields(ctx, Field::Milliseconds, false)
+}
+
+fn set_minutes<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  set_fields(ctx, Field::Minutes, false)
+}
+
+fn set_month<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  set_fields(ctx, Field::Month, false)
+}
+
+fn set_seconds<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  set_fields(ctx, Field::Seconds, false)
+}
+
+/// `Date.prototype.setTime(time)`
+fn set_time<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let this = match this_date(ctx) {
+    Some(this) => this,
+    None => return Ok(AvmValue::UNDEFINED),
+  };
+  let time = time_clip(ctx.arg(0).to_avm_number_with_context(ctx)?.value());
+  this.0.borrow_mut().value = Some(AvmValue::number(time));
+  Ok(AvmValue::number(time))
+}
+
+fn set_utc_date<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  set_fields(ctx, Field::Date, true)
+}
+
+fn set_utc_full_year<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  set_fields(ctx, Field::FullYear, true)
+}
+
+fn set_utc_hours<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  set_fields(ctx, Field::Hours, true)
+}
+
+fn set_utc_milliseconds<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  set_fields(ctx, Field::Milliseconds, true)
+}
+
+fn set_utc_minutes<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  set_fields(ctx, Field::Minutes, true)
+}
+
+fn set_utc_month<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  set_fields(ctx, Field::Month, true)
+}
+
+fn set_utc_seconds<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  set_fields(ctx, Field::Seconds, true)
+}
+
+/// `Date.prototype.setYear(year)`, two-digit years are relative to 1900.
+fn set_year<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let this = match this_date(ctx) {
+    Some(this) => this,
+    None => return Ok(AvmValue::UNDEFINED),
+  };
+  let mut year = ctx.arg(0).to_avm_number_with_context(ctx)?.value().trunc();
+  if (0f64..=99f64).contains(&year) {
+    year += 1900f64;
+  }
+  let time = this_time_in(ctx, false);
+  let mut fields = to_fields(if time.is_nan() { 0f64 } else { time });
+  fields[Field::FullYear as usize] = year;
+  let time = time_clip(from_fields(&fields) - local_offset(ctx));
+  this.0.borrow_mut().value = Some(AvmValue::number(time));
+  Ok(AvmValue::number(time))
+}
+
+/// `Date.prototype.toString()`, for example `Tue Feb 1 00:00:00 GMT-0800 2005`.
+fn to_string<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let time = this_time(ctx);
+  let offset = local_offset(ctx);
+  let result = format_date(time, offset);
+  Ok(AvmValue::String(ctx.string(result).unwrap()))
+}
+
+/// `Date.prototype.valueOf()` and `Date.prototype.getTime()`
+fn value_of<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  Ok(AvmValue::number(this_time(ctx)))
+}
+
+/// Returns the day of the week of a time value, `0` is Sunday.
+fn week_day(time: f64) -> f64 {
+  if time.is_nan() {
+    return f64::NAN;
+  }
+  ((time / MS_PER_DAY).floor() + 4f64).rem_euclid(7f64)
+}
+
+/// Formats a time value in local time, using the Flash Player format.
+fn format_date(time: f64, offset: f64) -> String {
+  if time.is_nan() {
+    return String::from("Invalid Date");
+  }
+  let local = time + offset;
+  let fields = to_fields(local);
+  let offset_minutes = (offset / MS_PER_MINUTE) as i64;
+  format!(
+    "{} {} {} {:02}:{:02}:{:02} GMT{}{:02}{:02} {}",
+    DAY_NAMES[week_day(local) as usize],
+    MONTH_NAMES[fields[Field::Month as usize] as usize],
+    fields[Field::Date as usize],
+    fields[Field::Hours as usize],
+    fields[Field::Minutes as usize],
+    fields[Field::Seconds as usize],
+    if offset_minutes < 0 { "-" } else { "+" },
+    offset_minutes.abs() / 60,
+    offset_minutes.abs() % 60,
+    fields[Field::FullYear as usize],
+  )
+}
@@ -0,0 +1,64 @@
+use scoped_gc::GcScope;
+
+use crate::context::{AvmResult, CallContext, CallType};
+use crate::realm::{define_methods, link_constructor, new_host_function, NativeFunction};
+use crate::values::{AvmObject, AvmValue};
+use crate::values::object::{AvmObjectRef, DONT_ENUM};
+
+// > 15.11 Error Objects
+
+pub struct ErrorRealm<'gc> {
+  /// `Error`
+  pub error: AvmObjectRef<'gc>,
+
+  /// `Error.prototype`
+  pub error_p: AvmObjectRef<'gc>,
+}
+
+impl<'gc> ErrorRealm<'gc> {
+  pub fn new(gc: &'gc GcScope<'gc>, obj_p: &AvmObjectRef<'gc>, func_p: &AvmObjectRef<'gc>) -> Self {
+    let error_p = AvmObject::new(gc, Some(obj_p.clone())).unwrap();
+    let methods: [(&str, NativeFunction<'gc>); 1] = [("toString", to_string)];
+    define_methods(gc, func_p, &error_p, &methods);
+    {
+      let mut error_p = error_p.0.borrow_mut();
+      error_p.define(String::from("message"), AvmValue::string(gc, String::from("Error")).unwrap(), DONT_ENUM);
+      error_p.define(String::from("name"), AvmValue::string(gc, String::from("Error")).unwrap(), DONT_ENUM);
+    }
+
+    let error = new_host_function(gc, func_p, error);
+    link_constructor(&error, &error_p);
+
+    ErrorRealm { error, error_p }
+  }
+}
+
+/// `Error(message)` and `new Error(message)`
+///
+/// The message is only set if it is defined, otherwise it is inherited from `Error.prototype`.
+/// Subclasses may call `Error` on their own instances to initialize them.
+fn error<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let error_p = ctx.realm().error.error_p.clone();
+  let this = match (ctx.call_type(), ctx.this()) {
+    (CallType::Construct, AvmValue::Object(this)) => this,
+    (CallType::Apply, AvmValue::Object(ref this)) if this.0.borrow().prototypes(ctx.max_prototype_depth()).iter().any(|p| p.ptr_eq(&error_p)) => {
+      this.clone()
+    }
+    _ => AvmObject::new(ctx.gc(), Some(error_p)).unwrap(),
+  };
+  match ctx.arg(0) {
+    AvmValue::Undefined(_) => {}
+    message => this.0.borrow_mut().set(String::from("message"), message),
+  }
+  Ok(AvmValue::Object(this))
+}
+
+/// `Error.prototype.toString()`, returns the message of the error.
+fn to_string<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let message = match ctx.this() {
+    AvmValue::Object(ref this) => this.0.borrow().get("message").unwrap_or(AvmValue::UNDEFINED),
+    _ => return Ok(AvmValue::UNDEFINED),
+  };
+  let message = message.to_avm_string(ctx)?;
+  Ok(AvmValue::String(message))
+}
@@ -0,0 +1,246 @@
+use crate::context::{AvmResult, CallContext};
+use crate::realm::array::read_items;
+use crate::timer::{Timer, TimerCallback};
+use crate::values::AvmValue;
+use crate::values::object::AvmObjectClass;
+
+/// `ASSetPropFlags(obj, props, set, clear)`
+///
+/// `props` is a comma-separated list of names, an array of names or `null` for all the own
+/// properties. The `clear` mask is applied before the `set` mask.
+pub(crate) fn as_set_prop_flags<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let target = match ctx.arg(0) {
+    AvmValue::Object(target) => target,
+    _ => return Ok(AvmValue::UNDEFINED),
+  };
+  let keys: Vec<String> = match ctx.arg(1) {
+    AvmValue::Null(_) => target.0.borrow().properties.iter().map(|(key, _)| key.clone()).collect(),
+    AvmValue::Object(ref names) if names.0.borrow().class == AvmObjectClass::Array => {
+      let mut keys: Vec<String> = Vec::new();
+      for name in read_items(names) {
+        keys.push(name.to_avm_string(ctx)?.value().to_string());
+      }
+      keys
+    }
+    names => names
+      .to_avm_string(ctx)?
+      .value()
+      .split(',')
+      .map(|key| key.trim().to_string())
+      .filter(|key| !key.is_empty())
+      .collect(),
+  };
+  let set_mask = ctx.arg(2).to_avm_number_with_context(ctx)?.to_int32() as u16;
+  let clear_mask = ctx.arg(3).to_avm_number_with_context(ctx)?.to_int32() as u16;
+
+  let mut target = target.0.borrow_mut();
+  for key in keys {
+    if let Some(property) = target.properties.get_mut(&key) {
+      let flags = (property.flags() & !clear_mask) | set_mask;
+      property.set_flags(flags);
+    }
+  }
+  Ok(AvmValue::UNDEFINED)
+}
+
+/// Returns the string value of the argument at `index`.
+fn string_arg<'gc>(ctx: &mut dyn CallContext<'gc>, index: usize) -> Result<String, AvmValue<'gc>> {
+  Ok(ctx.arg(index).to_avm_string(ctx)?.value().to_string())
+}
+
+/// `parseInt(string, radix)`
+///
+/// Without a radix, the `0x` prefix selects base 16 and a leading `0` followed only by octal
+/// digits selects base 8. Returns `NaN` if the radix is outside `[2, 36]` or no digit was read.
+pub(crate) fn parse_int<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let input = string_arg(ctx, 0)?;
+  let radix: Option<u32> = match ctx.arg(1) {
+    AvmValue::Undefined(_) => None,
+    radix => {
+      let radix = radix.to_avm_number_with_context(ctx)?.to_int32();
+      if !(2..=36).contains(&radix) {
+        return Ok(AvmValue::number(f64::NAN));
+      }
+      Some(radix as u32)
+    }
+  };
+
+  let mut s: &str = input.trim_start();
+  let mut sign = 1f64;
+  if s.starts_with('-') {
+    sign = -1f64;
+    s = &s[1..];
+  } else if s.starts_with('+') {
+    s = &s[1..];
+  }
+  let has_hex_prefix = s.starts_with("0x") || s.starts_with("0X");
+  let radix: u32 = match radix {
+    Some(16) | None if has_hex_prefix => {
+      s = &s[2..];
+      16
+    }
+    Some(radix) => radix,
+    None if s.len() > 1 && s.starts_with('0') && s.chars().all(|c| ('0'..='7').contains(&c)) => 8,
+    None => 10,
+  };
+
+  let mut result: Option<f64> = None;
+  for c in s.chars() {
+    match c.to_digit(radix) {
+      Some(digit) => result = Some(result.unwrap_or(0f64) * f64::from(radix) + f64::from(digit)),
+      None => break,
+    }
+  }
+  Ok(AvmValue::number(result.map_or(f64::NAN, |n| sign * n)))
+}
+
+/// `parseFloat(string)`, parses the longest decimal prefix after leading whitespace.
+pub(crate) fn parse_float<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let input = string_arg(ctx, 0)?;
+  let s: &str = input.trim_start();
+  let bytes = s.as_bytes();
+  let mut end = 0;
+  let skip_digits = |mut i: usize| -> usize {
+    while i < bytes.len() && bytes[i].is_ascii_digit() {
+      i += 1;
+    }
+    i
+  };
+
+  if end < bytes.len() && (bytes[end] == b'+' || bytes[end] == b'-') {
+    end += 1;
+  }
+  let int_end = skip_digits(end);
+  let mut has_digits = int_end > end;
+  end = int_end;
+  if end < bytes.len() && bytes[end] == b'.' {
+    let frac_end = skip_digits(end + 1);
+    has_digits = has_digits || frac_end > end + 1;
+    end = frac_end;
+  }
+  if !has_digits {
+    return Ok(AvmValue::number(f64::NAN));
+  }
+  if end < bytes.len() && (bytes[end] == b'e' || bytes[end] == b'E') {
+    let mut exp_start = end + 1;
+    if exp_start < bytes.len() && (bytes[exp_start] == b'+' || bytes[exp_start] == b'-') {
+      exp_start += 1;
+    }
+    let exp_end = skip_digits(exp_start);
+    if exp_end > exp_start {
+      end = exp_end;
+    }
+  }
+  let result = s[..end].parse::<f64>().unwrap_or(f64::NAN);
+  Ok(AvmValue::number(result))
+}
+
+/// `isNaN(value)`
+pub(crate) fn is_nan<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let value = ctx.arg(0).to_avm_number_with_context(ctx)?.value();
+  Ok(AvmValue::boolean(value.is_nan()))
+}
+
+/// `isFinite(value)`
+pub(crate) fn is_finite<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let value = ctx.arg(0).to_avm_number_with_context(ctx)?.value();
+  Ok(AvmValue::boolean(value.is_finite()))
+}
+
+/// `escape(string)`: every byte of the UTF-8 encoding except ASCII letters and digits becomes `%XX`.
+pub(crate) fn escape<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let input = string_arg(ctx, 0)?;
+  let mut result = String::with_capacity(input.len());
+  for byte in input.bytes() {
+    if byte.is_ascii_alphanumeric() {
+      result.push(byte as char);
+    } else {
+      result.push_str(&format!("%{:02X}", byte));
+    }
+  }
+  Ok(AvmValue::String(ctx.string(result).unwrap()))
+}
+
+/// `unescape(string)`: decodes the `%XX` sequences, invalid sequences are kept as-is.
+pub(crate) fn unescape<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let input = string_arg(ctx, 0)?;
+  let bytes = input.as_bytes();
+  let mut result: Vec<u8> = Vec::with_capacity(bytes.len());
+  let mut i = 0;
+  while i < bytes.len() {
+    let escaped = if bytes[i] == b'%' && i + 2 < bytes.len() {
+      let high = (bytes[i + 1] as char).to_digit(16);
+      let low = (bytes[i + 2] as char).to_digit(16);
+      high.and_then(|high| low.map(|low| (high * 16 + low) as u8))
+    } else {
+      None
+    };
+    match escaped {
+      Some(byte) => {
+        result.push(byte);
+        i += 3;
+      }
+      None => {
+        result.push(bytes[i]);
+        i += 1;
+      }
+    }
+  }
+  let result = String::from_utf8_lossy(&result).into_owned();
+  Ok(AvmValue::String(ctx.string(result).unwrap()))
+}
+
+/// `getVersion()`: returns the player version reported by the host (e.g. `"WIN 9,0,0,0"`).
+pub(crate) fn get_version<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let version = ctx.host().version();
+  Ok(AvmValue::String(ctx.string(version).unwrap()))
+}
+
+/// `trace(value)`, same as the `Trace` action.
+pub(crate) fn trace<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let message = match ctx.arg(0) {
+    AvmValue::Undefined(_) => String::from("undefined"),
+    value => value.to_avm_string(ctx)?.value().to_string(),
+  };
+  ctx.host().trace(&message);
+  Ok(AvmValue::UNDEFINED)
+}
+
+/// Creates a timer from the arguments of `setInterval` or `setTimeout`, returns its id.
+///
+/// The arguments are either `(func, delay, ...args)` or `(obj, "method", delay, ...args)`.
+/// Returns `undefined` if they match neither form.
+fn add_timer<'gc>(ctx: &mut dyn CallContext<'gc>, repeat: bool) -> AvmResult<'gc> {
+  let (callback, delay_index) = match ctx.arg(0) {
+    AvmValue::Object(ref func) if func.0.borrow().callable.is_some() => (TimerCallback::Function(func.clone()), 1),
+    AvmValue::Object(ref obj) => match ctx.arg(1) {
+      AvmValue::Undefined(_) | AvmValue::Null(_) => return Ok(AvmValue::UNDEFINED),
+      name => {
+        let name = name.to_avm_string(ctx)?.value().to_string();
+        (TimerCallback::Method(obj.clone(), name), 2)
+      }
+    },
+    _ => return Ok(AvmValue::UNDEFINED),
+  };
+  let interval = ctx.arg(delay_index).to_avm_number_with_context(ctx)?.value();
+  let args: Vec<AvmValue<'gc>> = ctx.args().iter().skip(delay_index + 1).cloned().collect();
+  let id = ctx.add_timer(Timer { callback, interval, args, repeat });
+  Ok(AvmValue::number(f64::from(id)))
+}
+
+/// `setInterval(func, delay, ...args)` or `setInterval(obj, "method", delay, ...args)`
+pub(crate) fn set_interval<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  add_timer(ctx, true)
+}
+
+/// `setTimeout(func, delay, ...args)` or `setTimeout(obj, "method", delay, ...args)`
+pub(crate) fn set_timeout<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  add_timer(ctx, false)
+}
+
+/// `clearInterval(id)` and `clearTimeout(id)`
+pub(crate) fn clear_interval<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let id = ctx.arg(0).to_avm_number_with_context(ctx)?.to_int32();
+  ctx.remove_timer(id);
+  Ok(AvmValue::UNDEFINED)
+}
@@ -0,0 +1,169 @@
+use scoped_gc::GcScope;
+
+use crate::context::{AvmResult, CallContext};
+use crate::realm::{define_methods, NativeFunction};
+use crate::values::{AvmObject, AvmValue};
+use crate::values::object::{AvmObjectRef, DONT_DELETE, DONT_ENUM, READ_ONLY};
+
+// > 15.8 The Math Object
+
+/// Creates the `Math` object.
+pub(crate) fn create_math<'gc>(
+  gc: &'gc GcScope<'gc>,
+  obj_p: &AvmObjectRef<'gc>,
+  func_p: &AvmObjectRef<'gc>,
+) -> AvmObjectRef<'gc> {
+  let math = AvmObject::new(gc, Some(obj_p.clone())).unwrap();
+  let methods: [(&str, NativeFunction<'gc>); 18] = [
+    ("abs", abs),
+    ("acos", acos),
+    ("asin", asin),
+    ("atan", atan),
+    ("atan2", atan2),
+    ("ceil", ceil),
+    ("cos", cos),
+    ("exp", exp),
+    ("floor", floor),
+    ("log", log),
+    ("max", max),
+    ("min", min),
+    ("pow", pow),
+    ("random", random),
+    ("round", round),
+    ("sin", sin),
+    ("sqrt", sqrt),
+    ("tan", tan),
+  ];
+  define_methods(gc, func_p, &math, &methods);
+
+  let constants: [(&str, f64); 8] = [
+    ("E", ::std::f64::consts::E),
+    ("LN10", ::std::f64::consts::LN_10),
+    ("LN2", ::std::f64::consts::LN_2),
+    ("LOG10E", ::std::f64::consts::LOG10_E),
+    ("LOG2E", ::std::f64::consts::LOG2_E),
+    ("PI", ::std::f64::consts::PI),
+    ("SQRT1_2", ::std::f64::consts::FRAC_1_SQRT_2),
+    ("SQRT2", ::std::f64::consts::SQRT_2),
+  ];
+  for (name, value) in constants.iter() {
+    let value = AvmValue::number(*value);
+    math.0.borrow_mut().define(String::from(*name), value, DONT_ENUM | DONT_DELETE | READ_ONLY);
+  }
+  math
+}
+
+/// Converts the argument at `index` to a number, a missing argument is `NaN`.
+fn number_arg<'gc>(ctx: &mut dyn CallContext<'gc>, index: usize) -> Result<f64, AvmValue<'gc>> {
+  Ok(ctx.arg(index).to_avm_number_with_context(ctx)?.value())
+}
+
+/// Applies `func` to the first argument.
+fn unary<'gc>(ctx: &mut dyn CallContext<'gc>, func: fn(f64) -> f64) -> AvmResult<'gc> {
+  let x = number_arg(ctx, 0)?;
+  Ok(AvmValue::number(func(x)))
+}
+
+fn abs<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  unary(ctx, f64::abs)
+}
+
+fn acos<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  unary(ctx, f64::acos)
+}
+
+fn asin<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  unary(ctx, f64::asin)
+}
+
+fn atan<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  unary(ctx, f64::atan)
+}
+
+/// `Math.atan2(y, x)`
+fn atan2<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let y = number_arg(ctx, 0)?;
+  let x = number_arg(ctx, 1)?;
+  Ok(AvmValue::number(y.atan2(x)))
+}
+
+fn ceil<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  unary(ctx, f64::ceil)
+}
+
+fn cos<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  unary(ctx, f64::cos)
+}
+
+fn exp<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  unary(ctx, f64::exp)
+}
+
+fn floor<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  unary(ctx, f64::floor)
+}
+
+fn log<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  unary(ctx, f64::ln)
+}
+
+/// Compares the first two arguments, as Flash Player ignores the others.
+///
+/// Without arguments, returns `empty`. With a single argument, returns `NaN`.
+fn compare_pair<'gc>(ctx: &mut dyn CallContext<'gc>, empty: f64, pick_first: fn(f64, f64) -> bool) -> AvmResult<'gc> {
+  let result = match ctx.args().len() {
+    0 => empty,
+    1 => f64::NAN,
+    _ => {
+      let a = number_arg(ctx, 0)?;
+      let b = number_arg(ctx, 1)?;
+      if a.is_nan() || b.is_nan() {
+        f64::NAN
+      } else if pick_first(a, b) {
+        a
+      } else {
+        b
+      }
+    }
+  };
+  Ok(AvmValue::number(result))
+}
+
+/// `Math.max(a, b)`
+fn max<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  compare_pair(ctx, f64::NEG_INFINITY, |a, b| a > b)
+}
+
+/// `Math.min(a, b)`
+fn min<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  compare_pair(ctx, f64::INFINITY, |a, b| a < b)
+}
+
+/// `Math.pow(x, y)`
+fn pow<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let x = number_arg(ctx, 0)?;
+  let y = number_arg(ctx, 1)?;
+  Ok(AvmValue::number(x.powf(y)))
+}
+
+/// `Math.random()`, uses the random number generator of the host.
+fn random<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  Ok(AvmValue::number(ctx.host().random()))
+}
+
+/// `Math.round(x)`, halves are rounded toward positive infinity (`Math.round(-2.5)` is `-2`).
+fn round<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  unary(ctx, |x| (x + 0.5).floor())
+}
+
+fn sin<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  unary(ctx, f64::sin)
+}
+
+fn sqrt<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  unary(ctx, f64::sqrt)
+}
+
+fn tan<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  unary(ctx, f64::tan)
+}
@@ -0,0 +1,436 @@
+use std::collections::HashMap;
+
+use scoped_gc::{GcRefCell, GcScope};
+
+use crate::context::{AvmResult, CallContext};
+use crate::values::{AvmNull, AvmObject, AvmValue};
+use crate::values::object::{AvmObjectClass, AvmObjectPrototype, AvmObjectRef, HostFunction, AvmCallable, DONT_DELETE, DONT_ENUM, READ_ONLY};
+use crate::values::property_map::PropertyMap;
+
+pub mod array;
+pub mod boolean;
+pub mod date;
+pub mod error;
+mod global;
+mod math;
+pub mod number;
+pub mod string;
+pub mod xml;
+
+/// Native implementation of a builtin function
+pub(crate) type NativeFunction<'gc> = fn(&mut dyn CallContext<'gc>) -> AvmResult<'gc>;
+
+pub struct Realm<'gc> {
+  /// `_global`
+  pub global: AvmObjectRef<'gc>,
+
+  /// `Object`
+  pub obj: AvmObjectRef<'gc>,
+
+  /// `Object.prototype`
+  pub obj_p: AvmObjectRef<'gc>,
+
+  /// `Function`
+  pub func: AvmObjectRef<'gc>,
+
+  /// `Function.prototype`
+  pub func_p: AvmObjectRef<'gc>,
+
+  pub array: array::ArrayRealm<'gc>,
+
+  pub boolean: boolean::BooleanRealm<'gc>,
+
+  pub date: date::DateRealm<'gc>,
+
+  pub error: error::ErrorRealm<'gc>,
+
+  /// `Math`
+  pub math: AvmObjectRef<'gc>,
+
+  pub number: number::NumberRealm<'gc>,
+
+  pub string: string::StringRealm<'gc>,
+
+  pub xml: xml::XmlRealm<'gc>,
+
+  /// `ASSetPropFlags`
+  pub as_set_prop_flags: AvmObjectRef<'gc>,
+}
+
+impl<'gc> Realm<'gc> {
+  pub fn new(gc: &'gc GcScope<'gc>) -> Self {
+    let obj_p = AvmObject {
+      class: AvmObjectClass::Object,
+      prototype: AvmObjectPrototype::Null(AvmNull),
+      properties: PropertyMap::new(),
+      callable: None,
+      watchers: HashMap::new(),
+      value: None,
+    };
+    let obj_p = gc.alloc(GcRefCell::new(obj_p)).map(AvmObjectRef).unwrap();
+
+    let func_p = AvmObject {
+      class: AvmObjectClass::Object,
+      prototype: AvmObjectPrototype::Object(obj_p.clone()),
+      properties: PropertyMap::new(),
+      callable: None,
+      watchers: HashMap::new(),
+      value: None,
+    };
+    let func_p = gc.alloc(GcRefCell::new(func_p)).map(AvmObjectRef).unwrap();
+
+    let obj = new_host_function(gc, &func_p, obj::object);
+    let obj_methods: [(&str, NativeFunction<'gc>); 1] = [("registerClass", obj::register_class)];
+    define_methods(gc, &func_p, &obj, &obj_methods);
+    link_constructor(&obj, &obj_p);
+
+    let func = new_host_function(gc, &func_p, func::function);
+    link_constructor(&func, &func_p);
+    let func_p_methods: [(&str, NativeFunction<'gc>); 2] = [("apply", func_p::apply), ("call", func_p::call)];
+    define_methods(gc, &func_p, &func_p, &func_p_methods);
+
+    let obj_p_methods: [(&str, NativeFunction<'gc>); 9] = [
+      ("addProperty", obj_p::add_property),
+      ("hasOwnProperty", obj_p::has_own_property),
+      ("isPropertyEnumerable", obj_p::is_property_enumerable),
+      ("isPrototypeOf", obj_p::is_prototype_of),
+      ("toLocaleString", obj_p::to_locale_string),
+      ("toString", obj_p::to_string),
+      ("unwatch", obj_p::unwatch),
+      ("valueOf", obj_p::value_of),
+      ("watch", obj_p::watch),
+    ];
+    define_methods(gc, &func_p, &obj_p, &obj_p_methods);
+
+    let array = array::ArrayRealm::new(gc, &obj_p, &func_p);
+    let boolean = boolean::BooleanRealm::new(gc, &obj_p, &func_p);
+    let date = date::DateRealm::new(gc, &obj_p, &func_p);
+    let error = error::ErrorRealm::new(gc, &obj_p, &func_p);
+    let math = math::create_math(gc, &obj_p, &func_p);
+    let number = number::NumberRealm::new(gc, &obj_p, &func_p);
+    let string = string::StringRealm::new(gc, &obj_p, &func_p);
+    let xml = xml::XmlRealm::new(gc, &obj_p, &func_p);
+
+    let as_set_prop_flags = new_host_function(gc, &func_p, global::as_set_prop_flags);
+
+    let global = AvmObject::new(gc, Some(obj_p.clone())).unwrap();
+    {
+      let mut global_obj = global.0.borrow_mut();
+      global_obj.define(String::from("_global"), AvmValue::Object(global.clone()), DONT_ENUM);
+      global_obj.define(String::from("ASSetPropFlags"), AvmValue::Object(as_set_prop_flags.clone()), DONT_ENUM);
+      global_obj.define(String::from("Array"), AvmValue::Object(array.array.clone()), DONT_ENUM);
+      global_obj.define(String::from("Boolean"), AvmValue::Object(boolean.boolean.clone()), DONT_ENUM);
+      global_obj.define(String::from("Date"), AvmValue::Object(date.date.clone()), DONT_ENUM);
+      global_obj.define(String::from("Error"), AvmValue::Object(error.error.clone()), DONT_ENUM);
+      global_obj.define(String::from("Function"), AvmValue::Object(func.clone()), DONT_ENUM);
+      global_obj.define(String::from("Math"), AvmValue::Object(math.clone()), DONT_ENUM);
+      global_obj.define(String::from("Number"), AvmValue::Object(number.number.clone()), DONT_ENUM);
+      global_obj.define(String::from("Object"), AvmValue::Object(obj.clone()), DONT_ENUM);
+      global_obj.define(String::from("String"), AvmValue::Object(string.string.clone()), DONT_ENUM);
+      global_obj.define(String::from("XML"), AvmValue::Object(xml.xml.clone()), DONT_ENUM);
+      global_obj.define(String::from("XMLNode"), AvmValue::Object(xml.xml_node.clone()), DONT_ENUM);
+      global_obj.define(String::from("Infinity"), AvmValue::number(f64::INFINITY), DONT_ENUM | DONT_DELETE | READ_ONLY);
+      global_obj.define(String::from("NaN"), AvmValue::number(f64::NAN), DONT_ENUM | DONT_DELETE | READ_ONLY);
+    }
+    let global_methods: [(&str, NativeFunction<'gc>); 12] = [
+      ("clearInterval", global::clear_interval),
+      ("clearTimeout", global::clear_interval),
+      ("escape", global::escape),
+      ("getVersion", global::get_version),
+      ("isFinite", global::is_finite),
+      ("isNaN", global::is_nan),
+      ("parseFloat", global::parse_float),
+      ("parseInt", global::parse_int),
+      ("setInterval", global::set_interval),
+      ("setTimeout", global::set_timeout),
+      ("trace", global::trace),
+      ("unescape", global::unescape),
+    ];
+    define_methods(gc, &func_p, &global, &global_methods);
+
+    Realm {
+      global,
+      obj,
+      obj_p,
+      func,
+      func_p,
+      array,
+      boolean,
+      date,
+      error,
+      math,
+      number,
+      string,
+      xml,
+      as_set_prop_flags,
+    }
+  }
+}
+
+/// Defines non-enumerable native methods on `target`.
+pub(crate) fn define_methods<'gc>(
+  gc: &'gc GcScope<'gc>,
+  func_p: &AvmObjectRef<'gc>,
+  target: &AvmObjectRef<'gc>,
+  methods: &[(&str, NativeFunction<'gc>)],
+) -> () {
+  for (name, func) in methods.iter() {
+    let method = new_host_function(gc, func_p, *func);
+    target.0.borrow_mut().define(String::from(*name), AvmValue::Object(method), DONT_ENUM);
+  }
+}
+
+/// Creates the `prototype` and `constructor` links between a constructor and its prototype.
+pub(crate) fn link_constructor<'gc>(constructor: &AvmObjectRef<'gc>, prototype: &AvmObjectRef<'gc>) -> () {
+  constructor.0.borrow_mut().define(String::from("prototype"), AvmValue::Object(prototype.clone()), DONT_ENUM | DONT_DELETE);
+  prototype.0.borrow_mut().define(String::from("constructor"), AvmValue::Object(constructor.clone()), DONT_ENUM);
+}
+
+/// Allocates a function object backed by the native function `func`.
+pub(crate) fn new_host_function<'gc>(
+  gc: &'gc GcScope<'gc>,
+  func_p: &AvmObjectRef<'gc>,
+  func: NativeFunction<'gc>,
+) -> AvmObjectRef<'gc> {
+  let host_function = AvmObject {
+    class: AvmObjectClass::Function,
+    prototype: AvmObjectPrototype::Object(func_p.clone()),
+    properties: PropertyMap::new(),
+    callable: Some(AvmCallable::HostFunction(HostFunction { func })),
+    watchers: HashMap::new(),
+    value: None,
+  };
+  gc.alloc(GcRefCell::new(host_function)).map(AvmObjectRef).unwrap()
+}
+
+mod obj_p {
+  use crate::context::{AvmResult, CallContext};
+  use crate::values::AvmValue;
+  use crate::values::object::{AvmAccessor, AvmObjectClass, AvmObjectProperty, AvmObjectRef, AvmWatcher};
+
+  pub(crate) fn to_string<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+    // 15.2.4.2 Object.prototype.toString ( )
+    // When the toString method is called, the following steps are taken:
+    // 1. Get the [[Class]] property of this object.
+    // 2. Compute a string value by concatenating the three strings "[object ", Result(1), and "]".
+    // 3. Return Result(2).
+    // Primitive values passed with `call` or `apply` are not boxed
+    let class: &'static str = match ctx.this() {
+      AvmValue::Object(v) => v.0.borrow().class.name(),
+      _ => AvmObjectClass::Object.name(),
+    };
+    let result = format!("[object {}]", class);
+    let result = ctx.string(result).unwrap();
+    Ok(AvmValue::String(result))
+  }
+
+  /// `Object.prototype.hasOwnProperty(name)`
+  pub(crate) fn has_own_property<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+    let target = match ctx.this() {
+      AvmValue::Object(target) => target,
+      _ => return Ok(AvmValue::FALSE),
+    };
+    let name = ctx.arg(0).to_avm_string(ctx)?.value().to_string();
+    let result = target.0.borrow().has_own_property(&name, ctx.swf_version());
+    Ok(AvmValue::boolean(result))
+  }
+
+  /// `Object.prototype.isPropertyEnumerable(name)`, only checks own properties.
+  pub(crate) fn is_property_enumerable<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+    let target = match ctx.this() {
+      AvmValue::Object(target) => target,
+      _ => return Ok(AvmValue::FALSE),
+    };
+    let name = ctx.arg(0).to_avm_string(ctx)?.value().to_string();
+    let swf_version = ctx.swf_version();
+    let result = target.0.borrow()
+      .get_local_property(&name)
+      .is_some_and(|p| p.enumerable && p.is_visible(swf_version));
+    Ok(AvmValue::boolean(result))
+  }
+
+  /// `Object.prototype.isPrototypeOf(value)`
+  pub(crate) fn is_prototype_of<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+    let (target, value) = match (ctx.this(), ctx.arg(0)) {
+      (AvmValue::Object(target), AvmValue::Object(value)) => (target, value),
+      _ => return Ok(AvmValue::FALSE),
+    };
+    let result = value.0.borrow().prototypes(ctx.max_prototype_depth()).iter().any(|p| p.ptr_eq(&target));
+    Ok(AvmValue::boolean(result))
+  }
+
+  /// `Object.prototype.toLocaleString()`, calls `this.toString()`.
+  pub(crate) fn to_locale_string<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+    let this = ctx.this();
+    let to_string = match this {
+      AvmValue::Object(ref target) => target.0.borrow().get("toString").unwrap_or(AvmValue::UNDEFINED),
+      _ => return Ok(AvmValue::UNDEFINED),
+    };
+    ctx.apply(to_string, this, &[])
+  }
+
+  /// `Object.prototype.valueOf()`
+  pub(crate) fn value_of<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+    Ok(ctx.this())
+  }
+
+  /// `Object.prototype.addProperty(name, getter, setter)`
+  ///
+  /// Returns `false` if the name is empty, the getter is not a function, the setter is neither
+  /// a function nor `null`, or the own property `name` is read-only. An existing property keeps
+  /// its other attributes.
+  pub(crate) fn add_property<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+    let target = match ctx.this() {
+      AvmValue::Object(target) => target,
+      _ => return Ok(AvmValue::FALSE),
+    };
+    let name = ctx.arg(0).to_avm_string(ctx)?.value().to_string();
+    let getter = match ctx.arg(1) {
+      AvmValue::Object(ref getter) if getter.0.borrow().callable.is_some() => getter.clone(),
+      _ => return Ok(AvmValue::FALSE),
+    };
+    let setter: Option<AvmObjectRef<'gc>> = match ctx.arg(2) {
+      AvmValue::Object(ref setter) if setter.0.borrow().callable.is_some() => Some(setter.clone()),
+      AvmValue::Null(_) => None,
+      _ => return Ok(AvmValue::FALSE),
+    };
+    if name.is_empty() {
+      return Ok(AvmValue::FALSE);
+    }
+    let mut target = target.0.borrow_mut();
+    match target.properties.get_mut(&name) {
+      Some(ref property) if property.read_only => return Ok(AvmValue::FALSE),
+      Some(property) => {
+        property.value = AvmValue::UNDEFINED;
+        property.accessor = Some(AvmAccessor { getter, setter });
+      }
+      None => {
+        target.properties.insert(name, AvmObjectProperty::accessor(getter, setter));
+      }
+    }
+    Ok(AvmValue::TRUE)
+  }
+
+  /// `Object.prototype.watch(name, callback, userData)`
+  pub(crate) fn watch<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+    let target = match ctx.this() {
+      AvmValue::Object(target) => target,
+      _ => return Ok(AvmValue::FALSE),
+    };
+    let name = ctx.arg(0).to_avm_string(ctx)?.value().to_string();
+    let callback = match ctx.arg(1) {
+      AvmValue::Object(ref callback) if callback.0.borrow().callable.is_some() => callback.clone(),
+      _ => return Ok(AvmValue::FALSE),
+    };
+    let watcher = AvmWatcher { callback, user_data: ctx.arg(2), running: false };
+    target.0.borrow_mut().watchers.insert(name, watcher);
+    Ok(AvmValue::TRUE)
+  }
+
+  /// `Object.prototype.unwatch(name)`, returns `true` if a watcher was removed.
+  pub(crate) fn unwatch<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+    let target = match ctx.this() {
+      AvmValue::Object(target) => target,
+      _ => return Ok(AvmValue::FALSE),
+    };
+    let name = ctx.arg(0).to_avm_string(ctx)?.value().to_string();
+    let removed = target.0.borrow_mut().watchers.remove(&name).is_some();
+    Ok(AvmValue::boolean(removed))
+  }
+}
+
+mod obj {
+  use crate::context::{AvmResult, CallContext};
+  use crate::values::{AvmObject, AvmValue};
+
+  /// `Object(value)` and `new Object(value)`: returns `value` if it is an object, or a new object.
+  pub(crate) fn object<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+    match ctx.arg(0) {
+      value @ AvmValue::Object(_) => Ok(value),
+      _ => {
+        let result = AvmObject::new(ctx.gc(), Some(ctx.realm().obj_p.clone())).unwrap();
+        Ok(AvmValue::Object(result))
+      }
+    }
+  }
+
+  /// `Object.registerClass(symbolName, constructor)`
+  ///
+  /// Associates a library symbol with a class, `null` removes the association.
+  pub(crate) fn register_class<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+    let name = match ctx.arg(0) {
+      AvmValue::Undefined(_) | AvmValue::Null(_) => return Ok(AvmValue::FALSE),
+      name => name.to_avm_string(ctx)?.value().to_string(),
+    };
+    match ctx.arg(1) {
+      AvmValue::Object(ref class) if class.0.borrow().callable.is_some() => {
+        ctx.register_class(name, Some(class.clone()))
+      }
+      AvmValue::Null(_) => ctx.register_class(name, None),
+      _ => return Ok(AvmValue::FALSE),
+    }
+    Ok(AvmValue::TRUE)
+  }
+}
+
+mod func {
+  use crate::context::{AvmResult, CallContext};
+  use crate::values::AvmValue;
+
+  /// `Function(value)`: returns `value` if it is an object, or the current `this`.
+  pub(crate) fn function<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+    match ctx.arg(0) {
+      value @ AvmValue::Object(_) => Ok(value),
+      _ => Ok(ctx.this()),
+    }
+  }
+}
+
+mod func_p {
+  use crate::context::{AvmResult, CallContext};
+  use crate::realm::array::read_items;
+  use crate::values::AvmValue;
+
+  /// Returns the `this` value for `call` and `apply`: `null` and `undefined` are replaced by `_global`.
+  fn this_arg<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmValue<'gc> {
+    match ctx.arg(0) {
+      AvmValue::Undefined(_) | AvmValue::Null(_) => AvmValue::Object(ctx.realm().global.clone()),
+      this_arg => this_arg,
+    }
+  }
+
+  /// Returns `this` if it is callable.
+  fn this_func<'gc>(ctx: &mut dyn CallContext<'gc>) -> Option<AvmValue<'gc>> {
+    match ctx.this() {
+      AvmValue::Object(ref func) if func.0.borrow().callable.is_some() => Some(AvmValue::Object(func.clone())),
+      _ => None,
+    }
+  }
+
+  /// `Function.prototype.apply(thisArg, args)`, `args` is an array-like object.
+  ///
+  /// Returns `undefined` if `this` is not a function.
+  pub(crate) fn apply<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+    let func = match this_func(ctx) {
+      Some(func) => func,
+      None => return Ok(AvmValue::UNDEFINED),
+    };
+    let this_arg = this_arg(ctx);
+    let args: Vec<AvmValue<'gc>> = match ctx.arg(1) {
+      AvmValue::Object(ref args) => read_items(args),
+      _ => Vec::new(),
+    };
+    ctx.apply(func, this_arg, &args)
+  }
+
+  /// `Function.prototype.call(thisArg, ...args)`
+  pub(crate) fn call<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+    let func = match this_func(ctx) {
+      Some(func) => func,
+      None => return Ok(AvmValue::UNDEFINED),
+    };
+    let this_arg = this_arg(ctx);
+    let args: Vec<AvmValue<'gc>> = ctx.args().iter().skip(1).cloned().collect();
+    ctx.apply(func, this_arg, &args)
+  }
+}
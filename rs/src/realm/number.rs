@@ -0,0 +1,117 @@
+use scoped_gc::GcScope;
+
+use crate::context::{AvmResult, CallContext, CallType};
+use crate::realm::{define_methods, link_constructor, new_host_function, NativeFunction};
+use crate::values::{number_to_string, AvmObject, AvmValue};
+use crate::values::object::{AvmObjectClass, AvmObjectRef, DONT_DELETE, DONT_ENUM, READ_ONLY};
+
+// > 15.7 Number Objects
+
+pub struct NumberRealm<'gc> {
+  /// `Number`
+  pub number: AvmObjectRef<'gc>,
+
+  /// `Number.prototype`
+  pub number_p: AvmObjectRef<'gc>,
+}
+
+impl<'gc> NumberRealm<'gc> {
+  pub fn new(gc: &'gc GcScope<'gc>, obj_p: &AvmObjectRef<'gc>, func_p: &AvmObjectRef<'gc>) -> Self {
+    let number_p = AvmObject::new(gc, Some(obj_p.clone())).unwrap();
+    let methods: [(&str, NativeFunction<'gc>); 2] = [("toString", to_string), ("valueOf", value_of)];
+    define_methods(gc, func_p, &number_p, &methods);
+
+    let number = new_host_function(gc, func_p, number);
+    let constants: [(&str, f64); 5] = [
+      ("MAX_VALUE", f64::MAX),
+      ("MIN_VALUE", 5e-324),
+      ("NaN", f64::NAN),
+      ("NEGATIVE_INFINITY", f64::NEG_INFINITY),
+      ("POSITIVE_INFINITY", f64::INFINITY),
+    ];
+    for (name, value) in constants.iter() {
+      let value = AvmValue::number(*value);
+      number.0.borrow_mut().define(String::from(*name), value, DONT_ENUM | DONT_DELETE | READ_ONLY);
+    }
+    link_constructor(&number, &number_p);
+
+    NumberRealm { number, number_p }
+  }
+}
+
+/// `Number(value)` converts `value` to a number, `new Number(value)` creates a Number object.
+fn number<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let value = match ctx.args().first().cloned() {
+    Some(value) => AvmValue::Number(value.to_avm_number_with_context(ctx)?),
+    None => AvmValue::number(0f64),
+  };
+  if ctx.call_type() == CallType::Apply {
+    return Ok(value);
+  }
+  let this = match ctx.this() {
+    AvmValue::Object(this) => this,
+    _ => return Ok(value),
+  };
+  {
+    let mut this = this.0.borrow_mut();
+    this.class = AvmObjectClass::Number;
+    this.value = Some(value);
+  }
+  Ok(AvmValue::Object(this))
+}
+
+/// Returns the number value of `this`, a primitive number or a Number object.
+fn this_number<'gc>(ctx: &mut dyn CallContext<'gc>) -> Option<f64> {
+  match ctx.this() {
+    AvmValue::Number(value) => Some(value.value()),
+    AvmValue::Object(ref this) if this.0.borrow().class == AvmObjectClass::Number => match this.0.borrow().value {
+      Some(AvmValue::Number(value)) => Some(value.value()),
+      _ => None,
+    },
+    _ => None,
+  }
+}
+
+/// `Number.prototype.toString(radix)`
+///
+/// For radixes other than 10, the value is truncated to an integer. An invalid radix is treated
+/// as 10.
+fn to_string<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let value = match this_number(ctx) {
+    Some(value) => value,
+    None => return Ok(AvmValue::UNDEFINED),
+  };
+  let radix: i32 = match ctx.arg(0) {
+    AvmValue::Undefined(_) => 10,
+    radix => radix.to_avm_number_with_context(ctx)?.to_int32(),
+  };
+  let result = if !(2..=36).contains(&radix) || radix == 10 || !value.is_finite() {
+    number_to_string(value)
+  } else {
+    integer_to_string(value.trunc(), radix as u32)
+  };
+  Ok(AvmValue::String(ctx.string(result).unwrap()))
+}
+
+/// Formats an integer in the base `radix`, using lowercase letters for the digits above 9.
+fn integer_to_string(value: f64, radix: u32) -> String {
+  let mut n = value.abs();
+  let mut digits: Vec<char> = Vec::new();
+  loop {
+    let digit = (n % f64::from(radix)) as u32;
+    digits.push(::std::char::from_digit(digit, radix).unwrap());
+    n = (n / f64::from(radix)).trunc();
+    if n < 1f64 {
+      break;
+    }
+  }
+  if value < 0f64 {
+    digits.push('-');
+  }
+  digits.iter().rev().collect()
+}
+
+/// `Number.prototype.valueOf()`
+fn value_of<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  Ok(this_number(ctx).map_or(AvmValue::UNDEFINED, AvmValue::number))
+}
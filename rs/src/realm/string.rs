@@ -0,0 +1,283 @@
+use scoped_gc::GcScope;
+
+use crate::context::{AvmResult, CallContext, CallType};
+use crate::realm::array::new_array;
+use crate::realm::{define_methods, link_constructor, new_host_function, NativeFunction};
+use crate::values::{AvmObject, AvmValue};
+use crate::values::object::{AvmObjectClass, AvmObjectRef, DONT_DELETE, DONT_ENUM, READ_ONLY};
+
+// > 15.5 String Objects
+//
+// Indices and lengths are expressed in UTF-16 code units, as in Flash Player.
+
+pub struct StringRealm<'gc> {
+  /// `String`
+  pub string: AvmObjectRef<'gc>,
+
+  /// `String.prototype`
+  pub string_p: AvmObjectRef<'gc>,
+}
+
+impl<'gc> StringRealm<'gc> {
+  pub fn new(gc: &'gc GcScope<'gc>, obj_p: &AvmObjectRef<'gc>, func_p: &AvmObjectRef<'gc>) -> Self {
+    let string_p = AvmObject::new(gc, Some(obj_p.clone())).unwrap();
+    let methods: [(&str, NativeFunction<'gc>); 13] = [
+      ("charAt", char_at),
+      ("charCodeAt", char_code_at),
+      ("concat", concat),
+      ("indexOf", index_of),
+      ("lastIndexOf", last_index_of),
+      ("slice", slice),
+      ("split", split),
+      ("substr", substr),
+      ("substring", substring),
+      ("toLowerCase", to_lower_case),
+      ("toString", to_string),
+      ("toUpperCase", to_upper_case),
+      ("valueOf", to_string),
+    ];
+    define_methods(gc, func_p, &string_p, &methods);
+
+    let string = new_host_function(gc, func_p, string);
+    let string_methods: [(&str, NativeFunction<'gc>); 1] = [("fromCharCode", from_char_code)];
+    define_methods(gc, func_p, &string, &string_methods);
+    link_constructor(&string, &string_p);
+
+    StringRealm { string, string_p }
+  }
+}
+
+/// Returns the length of a string in UTF-16 code units (`String.prototype.length`).
+pub(crate) fn string_length(value: &str) -> usize {
+  value.encode_utf16().count()
+}
+
+/// `String(value)` converts `value` to a string, `new String(value)` creates a String object.
+fn string<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let value = match ctx.args().first().cloned() {
+    Some(value) => value.to_avm_string(ctx)?,
+    None => ctx.string(String::new()).unwrap(),
+  };
+  if ctx.call_type() == CallType::Apply {
+    return Ok(AvmValue::String(value));
+  }
+  let this = match ctx.this() {
+    AvmValue::Object(this) => this,
+    _ => return Ok(AvmValue::String(value)),
+  };
+  {
+    let mut this = this.0.borrow_mut();
+    this.class = AvmObjectClass::String;
+    let length = AvmValue::number(string_length(value.value()) as f64);
+    this.define(String::from("length"), length, DONT_ENUM | DONT_DELETE | READ_ONLY);
+    this.value = Some(AvmValue::String(value));
+  }
+  Ok(AvmValue::Object(this))
+}
+
+/// Returns the UTF-16 code units of `this` converted to a string.
+fn this_units<'gc>(ctx: &mut dyn CallContext<'gc>) -> Result<Vec<u16>, AvmValue<'gc>> {
+  let this = ctx.this();
+  Ok(this.to_avm_string(ctx)?.value().encode_utf16().collect())
+}
+
+fn string_arg<'gc>(ctx: &mut dyn CallContext<'gc>, index: usize) -> Result<String, AvmValue<'gc>> {
+  Ok(ctx.arg(index).to_avm_string(ctx)?.value().to_string())
+}
+
+/// Converts an argument to an integer, `NaN` becomes `0`.
+fn to_integer<'gc>(ctx: &mut dyn CallContext<'gc>, index: usize) -> Result<f64, AvmValue<'gc>> {
+  let value = ctx.arg(index).to_avm_number_with_context(ctx)?.value();
+  Ok(if value.is_nan() { 0f64 } else { value.trunc() })
+}
+
+/// Resolves a possibly negative index relative to the end of a string of length `len`.
+fn relative_index(index: f64, len: usize) -> usize {
+  if index < 0f64 {
+    (len as f64 + index).max(0f64) as usize
+  } else {
+    index.min(len as f64) as usize
+  }
+}
+
+fn from_units<'gc>(ctx: &mut dyn CallContext<'gc>, units: &[u16]) -> AvmResult<'gc> {
+  Ok(AvmValue::String(ctx.string(String::from_utf16_lossy(units)).unwrap()))
+}
+
+/// Returns the position of `needle` in `units`, starting the search at `start`.
+fn find_units(units: &[u16], needle: &[u16], start: usize) -> Option<usize> {
+  if needle.len() > units.len() {
+    return None;
+  }
+  (start..=units.len() - needle.len()).find(|&i| &units[i..i + needle.len()] == needle)
+}
+
+/// `String.fromCharCode(...codes)`
+fn from_char_code<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let mut units: Vec<u16> = Vec::with_capacity(ctx.args().len());
+  for code in ctx.args().to_vec() {
+    units.push(code.to_avm_number_with_context(ctx)?.to_int32() as u16);
+  }
+  from_units(ctx, &units)
+}
+
+/// `String.prototype.toString()` and `String.prototype.valueOf()`
+fn to_string<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  match ctx.this() {
+    this @ AvmValue::String(_) => Ok(this),
+    AvmValue::Object(ref this) if this.0.borrow().class == AvmObjectClass::String => {
+      Ok(this.0.borrow().value.clone().unwrap_or(AvmValue::UNDEFINED))
+    }
+    _ => Ok(AvmValue::UNDEFINED),
+  }
+}
+
+/// `String.prototype.charAt(index)`, returns the empty string if `index` is out of bounds.
+fn char_at<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let units = this_units(ctx)?;
+  let index = to_integer(ctx, 0)?;
+  if index < 0f64 || index >= units.len() as f64 {
+    return from_units(ctx, &[]);
+  }
+  let index = index as usize;
+  from_units(ctx, &units[index..index + 1])
+}
+
+/// `String.prototype.charCodeAt(index)`, returns `NaN` if `index` is out of bounds.
+fn char_code_at<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let units = this_units(ctx)?;
+  let index = to_integer(ctx, 0)?;
+  if index < 0f64 || index >= units.len() as f64 {
+    return Ok(AvmValue::number(f64::NAN));
+  }
+  Ok(AvmValue::number(f64::from(units[index as usize])))
+}
+
+/// `String.prototype.concat(...values)`
+fn concat<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let this = ctx.this();
+  let mut result = this.to_avm_string(ctx)?.value().to_string();
+  for arg in ctx.args().to_vec() {
+    result.push_str(arg.to_avm_string(ctx)?.value());
+  }
+  Ok(AvmValue::String(ctx.string(result).unwrap()))
+}
+
+/// `String.prototype.indexOf(value, start)`, a negative `start` is treated as `0`.
+fn index_of<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let units = this_units(ctx)?;
+  let needle: Vec<u16> = string_arg(ctx, 0)?.encode_utf16().collect();
+  let start = to_integer(ctx, 1)?.max(0f64);
+  if start > units.len() as f64 {
+    return Ok(AvmValue::number(-1f64));
+  }
+  let result = find_units(&units, &needle, start as usize).map_or(-1f64, |i| i as f64);
+  Ok(AvmValue::number(result))
+}
+
+/// `String.prototype.lastIndexOf(value, start)`, a negative `start` always returns `-1`.
+fn last_index_of<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let units = this_units(ctx)?;
+  let needle: Vec<u16> = string_arg(ctx, 0)?.encode_utf16().collect();
+  let start: f64 = match ctx.arg(1) {
+    AvmValue::Undefined(_) => units.len() as f64,
+    _ => to_integer(ctx, 1)?,
+  };
+  if start < 0f64 || needle.len() > units.len() {
+    return Ok(AvmValue::number(-1f64));
+  }
+  let start = start.min((units.len() - needle.len()) as f64) as usize;
+  let result = (0..=start)
+    .rev()
+    .find(|&i| &units[i..i + needle.len()] == needle.as_slice())
+    .map_or(-1f64, |i| i as f64);
+  Ok(AvmValue::number(result))
+}
+
+/// `String.prototype.slice(start, end)`, negative indices are relative to the end of the string.
+fn slice<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let units = this_units(ctx)?;
+  let len = units.len();
+  let start = relative_index(to_integer(ctx, 0)?, len);
+  let end = match ctx.arg(1) {
+    AvmValue::Undefined(_) => len,
+    _ => relative_index(to_integer(ctx, 1)?, len),
+  };
+  if start >= end {
+    return from_units(ctx, &[]);
+  }
+  from_units(ctx, &units[start..end])
+}
+
+/// `String.prototype.substr(start, length)`
+///
+/// Negative values of both `start` and `length` are relative to the end of the string.
+fn substr<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let units = this_units(ctx)?;
+  let len = units.len();
+  let start = relative_index(to_integer(ctx, 0)?, len);
+  let length = match ctx.arg(1) {
+    AvmValue::Undefined(_) => len,
+    _ => relative_index(to_integer(ctx, 1)?, len),
+  };
+  let end = (start + length).min(len);
+  from_units(ctx, &units[start..end])
+}
+
+/// `String.prototype.substring(start, end)`
+///
+/// Negative and `NaN` indices are treated as `0`, the indices are swapped if `start > end`.
+fn substring<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let units = this_units(ctx)?;
+  let len = units.len();
+  let start = to_integer(ctx, 0)?.max(0f64).min(len as f64) as usize;
+  let end = match ctx.arg(1) {
+    AvmValue::Undefined(_) => len,
+    _ => to_integer(ctx, 1)?.max(0f64).min(len as f64) as usize,
+  };
+  let (start, end) = if start > end { (end, start) } else { (start, end) };
+  from_units(ctx, &units[start..end])
+}
+
+/// `String.prototype.split(delimiter, limit)`
+///
+/// Without a delimiter, the result contains the whole string. An empty delimiter splits the
+/// string into its characters.
+fn split<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let this = ctx.this();
+  let value = this.to_avm_string(ctx)?.value().to_string();
+  let limit: usize = match ctx.arg(1) {
+    AvmValue::Undefined(_) => usize::MAX,
+    _ => to_integer(ctx, 1)?.max(0f64) as usize,
+  };
+  let parts: Vec<String> = match ctx.arg(0) {
+    AvmValue::Undefined(_) => vec![value],
+    delimiter => {
+      let delimiter = delimiter.to_avm_string(ctx)?.value().to_string();
+      if delimiter.is_empty() {
+        value.encode_utf16().map(|unit| String::from_utf16_lossy(&[unit])).collect()
+      } else {
+        value.split(delimiter.as_str()).map(String::from).collect()
+      }
+    }
+  };
+  let mut items: Vec<AvmValue<'gc>> = Vec::new();
+  for part in parts.into_iter().take(limit) {
+    items.push(AvmValue::String(ctx.string(part).unwrap()));
+  }
+  Ok(AvmValue::Object(new_array(ctx, items)))
+}
+
+/// `String.prototype.toLowerCase()`
+fn to_lower_case<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let this = ctx.this();
+  let result = this.to_avm_string(ctx)?.value().to_lowercase();
+  Ok(AvmValue::String(ctx.string(result).unwrap()))
+}
+
+/// `String.prototype.toUpperCase()`
+fn to_upper_case<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let this = ctx.this();
+  let result = this.to_avm_string(ctx)?.value().to_uppercase();
+  Ok(AvmValue::String(ctx.string(result).unwrap()))
+}
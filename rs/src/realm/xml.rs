@@ -0,0 +1,840 @@
+use scoped_gc::GcScope;
+
+use crate::context::{AvmResult, CallContext, CallType};
+use crate::realm::array::{insert_item, new_array, read_items, read_length, remove_item, write_items};
+use crate::realm::{define_methods, link_constructor, new_host_function, NativeFunction};
+use crate::values::{AvmObject, AvmValue};
+use crate::values::object::{AvmObjectClass, AvmObjectRef, DONT_ENUM, READ_ONLY};
+
+use self::parser::{ParsedNode, ParseResult};
+
+// XML and XMLNode classes (AS2 legacy XML DOM)
+//
+// The tree is stored in regular properties of the node objects: `childNodes` is an Array, and the
+// navigation properties (`parentNode`, `firstChild`, `nextSibling`, ...) are read-only properties
+// updated by the mutation methods.
+
+/// `XMLNode.ELEMENT_NODE`
+const ELEMENT_NODE: f64 = 1f64;
+/// `XMLNode.TEXT_NODE`
+const TEXT_NODE: f64 = 3f64;
+
+pub struct XmlRealm<'gc> {
+  /// `XML`
+  pub xml: AvmObjectRef<'gc>,
+
+  /// `XML.prototype`
+  pub xml_p: AvmObjectRef<'gc>,
+
+  /// `XMLNode`
+  pub xml_node: AvmObjectRef<'gc>,
+
+  /// `XMLNode.prototype`
+  pub xml_node_p: AvmObjectRef<'gc>,
+}
+
+impl<'gc> XmlRealm<'gc> {
+  pub fn new(gc: &'gc GcScope<'gc>, obj_p: &AvmObjectRef<'gc>, func_p: &AvmObjectRef<'gc>) -> Self {
+    let xml_node_p = AvmObject::new(gc, Some(obj_p.clone())).unwrap();
+    let node_methods: [(&str, NativeFunction<'gc>); 6] = [
+      ("appendChild", append_child),
+      ("cloneNode", clone_node),
+      ("hasChildNodes", has_child_nodes),
+      ("insertBefore", insert_before),
+      ("removeNode", remove_node),
+      ("toString", to_string),
+    ];
+    define_methods(gc, func_p, &xml_node_p, &node_methods);
+    let xml_node = new_host_function(gc, func_p, xml_node);
+    link_constructor(&xml_node, &xml_node_p);
+
+    let xml_p = AvmObject::new(gc, Some(xml_node_p.clone())).unwrap();
+    let methods: [(&str, NativeFunction<'gc>); 7] = [
+      ("createElement", create_element),
+      ("createTextNode", create_text_node),
+      ("load", load),
+      ("onData", on_data),
+      ("onLoad", on_load),
+      ("parseXML", parse_xml),
+      ("sendAndLoad", send_and_load),
+    ];
+    define_methods(gc, func_p, &xml_p, &methods);
+    {
+      let mut xml_p = xml_p.0.borrow_mut();
+      xml_p.define(String::from("ignoreWhite"), AvmValue::FALSE, DONT_ENUM);
+      let content_type = AvmValue::string(gc, String::from("application/x-www-form-urlencoded")).unwrap();
+      xml_p.define(String::from("contentType"), content_type, DONT_ENUM);
+    }
+    let xml = new_host_function(gc, func_p, xml);
+    link_constructor(&xml, &xml_p);
+
+    XmlRealm { xml, xml_p, xml_node, xml_node_p }
+  }
+}
+
+/// Defines the properties of a node without parent nor children.
+fn init_node<'gc>(ctx: &mut dyn CallContext<'gc>, node: &AvmObjectRef<'gc>, node_type: f64, name: AvmValue<'gc>, value: AvmValue<'gc>) -> () {
+  let attributes = AvmObject::new(ctx.gc(), Some(ctx.realm().obj_p.clone())).unwrap();
+  let child_nodes = new_array(ctx, Vec::new());
+  let mut node = node.0.borrow_mut();
+  node.define(String::from("nodeType"), AvmValue::number(node_type), DONT_ENUM | READ_ONLY);
+  node.define(String::from("nodeName"), name, DONT_ENUM);
+  node.define(String::from("nodeValue"), value, DONT_ENUM);
+  node.define(String::from("attributes"), AvmValue::Object(attributes), DONT_ENUM);
+  node.define(String::from("childNodes"), AvmValue::Object(child_nodes), DONT_ENUM | READ_ONLY);
+  for key in &["parentNode", "firstChild", "lastChild", "previousSibling", "nextSibling"] {
+    node.define(String::from(*key), AvmValue::NULL, DONT_ENUM | READ_ONLY);
+  }
+}
+
+/// Creates an element (`name` is a string) or a text node (`value` is a string).
+fn new_node<'gc>(ctx: &mut dyn CallContext<'gc>, node_type: f64, name: AvmValue<'gc>, value: AvmValue<'gc>) -> AvmObjectRef<'gc> {
+  let node = AvmObject::new(ctx.gc(), Some(ctx.realm().xml.xml_node_p.clone())).unwrap();
+  node.0.borrow_mut().class = AvmObjectClass::XMLNode;
+  init_node(ctx, &node, node_type, name, value);
+  node
+}
+
+/// Returns `Some(node)` if `value` is an XML node or document.
+fn as_node<'gc>(value: &AvmValue<'gc>) -> Option<AvmObjectRef<'gc>> {
+  match value {
+    AvmValue::Object(ref obj) => match obj.0.borrow().class {
+      AvmObjectClass::XML | AvmObjectClass::XMLNode => Some(obj.clone()),
+      _ => None,
+    },
+    _ => None,
+  }
+}
+
+fn this_node<'gc>(ctx: &mut dyn CallContext<'gc>) -> Option<AvmObjectRef<'gc>> {
+  as_node(&ctx.this())
+}
+
+fn string_arg<'gc>(ctx: &mut dyn CallContext<'gc>, index: usize) -> Result<String, AvmValue<'gc>> {
+  Ok(ctx.arg(index).to_avm_string(ctx)?.value().to_string())
+}
+
+fn get<'gc>(node: &AvmObjectRef<'gc>, key: &str) -> AvmValue<'gc> {
+  node.0.borrow().get(key).unwrap_or(AvmValue::UNDEFINED)
+}
+
+/// Returns the children of `node`.
+fn children<'gc>(node: &AvmObjectRef<'gc>) -> Vec<AvmObjectRef<'gc>> {
+  match get(node, "childNodes") {
+    AvmValue::Object(ref child_nodes) => read_items(child_nodes).iter().filter_map(as_node).collect(),
+    _ => Vec::new(),
+  }
+}
+
+fn or_null<'gc>(node: Option<&AvmObjectRef<'gc>>) -> AvmValue<'gc> {
+  node.map_or(AvmValue::NULL, |n| AvmValue::Object(n.clone()))
+}
+
+fn set_link<'gc>(node: &AvmObjectRef<'gc>, key: &str, value: AvmValue<'gc>) -> () {
+  node.0.borrow_mut().define(String::from(key), value, DONT_ENUM | READ_ONLY);
+}
+
+/// Replaces the children of `node` and sets their navigation properties.
+///
+/// The previous children, if any, must be detached by the caller.
+fn set_children<'gc>(node: &AvmObjectRef<'gc>, children: Vec<AvmObjectRef<'gc>>) -> () {
+  for (i, child) in children.iter().enumerate() {
+    let previous = if i > 0 { children.get(i - 1) } else { None };
+    set_link(child, "parentNode", AvmValue::Object(node.clone()));
+    set_link(child, "previousSibling", or_null(previous));
+    set_link(child, "nextSibling", or_null(children.get(i + 1)));
+  }
+  set_link(node, "firstChild", or_null(children.first()));
+  set_link(node, "lastChild", or_null(children.last()));
+  if let AvmValue::Object(ref child_nodes) = get(node, "childNodes") {
+    write_items(child_nodes, children.into_iter().map(AvmValue::Object).collect());
+  }
+}
+
+/// Returns the index of `child` in the `childNodes` array `child_nodes`.
+fn child_index<'gc>(child_nodes: &AvmObjectRef<'gc>, child: &AvmObjectRef<'gc>) -> Option<u32> {
+  let child_nodes = child_nodes.0.borrow();
+  (0..child_nodes.array_length()).find(|i| match child_nodes.get_local(&i.to_string()) {
+    Some(AvmValue::Object(ref item)) => item.ptr_eq(child),
+    _ => false,
+  })
+}
+
+/// Clears the parent and sibling properties of `node`.
+fn clear_links<'gc>(node: &AvmObjectRef<'gc>) -> () {
+  for key in &["parentNode", "previousSibling", "nextSibling"] {
+    set_link(node, key, AvmValue::NULL);
+  }
+}
+
+/// Detaches `node` from its parent, if any.
+///
+/// Only the siblings next to `node` are updated.
+fn detach<'gc>(node: &AvmObjectRef<'gc>) -> () {
+  let parent = match as_node(&get(node, "parentNode")) {
+    Some(parent) => parent,
+    None => return,
+  };
+  let previous = as_node(&get(node, "previousSibling"));
+  let next = as_node(&get(node, "nextSibling"));
+  match previous {
+    Some(ref previous) => set_link(previous, "nextSibling", or_null(next.as_ref())),
+    None => set_link(&parent, "firstChild", or_null(next.as_ref())),
+  }
+  match next {
+    Some(ref next) => set_link(next, "previousSibling", or_null(previous.as_ref())),
+    None => set_link(&parent, "lastChild", or_null(previous.as_ref())),
+  }
+  if let AvmValue::Object(ref child_nodes) = get(&parent, "childNodes") {
+    if let Some(index) = child_index(child_nodes, node) {
+      remove_item(child_nodes, index);
+    }
+  }
+  clear_links(node);
+}
+
+/// Returns `true` if `node` is `ancestor` or one of its descendants.
+fn is_within<'gc>(node: &AvmObjectRef<'gc>, ancestor: &AvmObjectRef<'gc>) -> bool {
+  let mut current = Some(node.clone());
+  while let Some(node) = current {
+    if node.ptr_eq(ancestor) {
+      return true;
+    }
+    current = as_node(&get(&node, "parentNode"));
+  }
+  false
+}
+
+/// Moves `child` into `parent`, before `before` or at the end.
+///
+/// Inserting a node into itself or one of its descendants is ignored.
+fn insert<'gc>(parent: &AvmObjectRef<'gc>, child: &AvmObjectRef<'gc>, before: Option<&AvmObjectRef<'gc>>) -> () {
+  if is_within(parent, child) {
+    return;
+  }
+  detach(child);
+  let child_nodes = match get(parent, "childNodes") {
+    AvmValue::Object(child_nodes) => child_nodes,
+    _ => return,
+  };
+  let (previous, next, index) = match before.and_then(|before| child_index(&child_nodes, before).map(|index| (before, index))) {
+    Some((before, index)) => (as_node(&get(before, "previousSibling")), Some(before), index),
+    None => (as_node(&get(parent, "lastChild")), None, read_length(&child_nodes)),
+  };
+  set_link(child, "parentNode", AvmValue::Object(parent.clone()));
+  set_link(child, "previousSibling", or_null(previous.as_ref()));
+  set_link(child, "nextSibling", or_null(next));
+  match previous {
+    Some(ref previous) => set_link(previous, "nextSibling", AvmValue::Object(child.clone())),
+    None => set_link(parent, "firstChild", AvmValue::Object(child.clone())),
+  }
+  match next {
+    Some(next) => set_link(next, "previousSibling", AvmValue::Object(child.clone())),
+    None => set_link(parent, "lastChild", AvmValue::Object(child.clone())),
+  }
+  insert_item(&child_nodes, index, AvmValue::Object(child.clone()));
+}
+
+/// `new XMLNode(type, value)`: `value` is the name of an element or the text of a text node.
+fn xml_node<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let node_type = ctx.arg(0).to_avm_number_with_context(ctx)?.value();
+  let value = ctx.arg(1).to_avm_string(ctx)?;
+  let (node_type, name, value) = if node_type == TEXT_NODE {
+    (TEXT_NODE, AvmValue::NULL, AvmValue::String(value))
+  } else {
+    (ELEMENT_NODE, AvmValue::String(value), AvmValue::NULL)
+  };
+  match (ctx.call_type(), ctx.this()) {
+    (CallType::Construct, AvmValue::Object(this)) => {
+      this.0.borrow_mut().class = AvmObjectClass::XMLNode;
+      init_node(ctx, &this, node_type, name, value);
+      Ok(AvmValue::Object(this))
+    }
+    _ => Ok(AvmValue::Object(new_node(ctx, node_type, name, value))),
+  }
+}
+
+/// `new XML(source)`: creates a document, parsing `source` if it is defined.
+fn xml<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let this = match (ctx.call_type(), ctx.this()) {
+    (CallType::Construct, AvmValue::Object(this)) => this,
+    _ => AvmObject::new(ctx.gc(), Some(ctx.realm().xml.xml_p.clone())).unwrap(),
+  };
+  this.0.borrow_mut().class = AvmObjectClass::XML;
+  init_node(ctx, &this, ELEMENT_NODE, AvmValue::NULL, AvmValue::NULL);
+  this.0.borrow_mut().set(String::from("status"), AvmValue::number(0f64));
+  match ctx.arg(0) {
+    AvmValue::Undefined(_) => {}
+    source => {
+      let source = source.to_avm_string(ctx)?.value().to_string();
+      parse_into(ctx, &this, &source);
+    }
+  }
+  Ok(AvmValue::Object(this))
+}
+
+/// `XMLNode.prototype.appendChild(child)`
+fn append_child<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  if let (Some(this), Some(child)) = (this_node(ctx), as_node(&ctx.arg(0))) {
+    insert(&this, &child, None);
+  }
+  Ok(AvmValue::UNDEFINED)
+}
+
+/// `XMLNode.prototype.insertBefore(child, before)`, does nothing if `before` is not a child.
+fn insert_before<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  if let (Some(this), Some(child), Some(before)) = (this_node(ctx), as_node(&ctx.arg(0)), as_node(&ctx.arg(1))) {
+    if children(&this).iter().any(|c| c.ptr_eq(&before)) {
+      insert(&this, &child, Some(&before));
+    }
+  }
+  Ok(AvmValue::UNDEFINED)
+}
+
+/// `XMLNode.prototype.removeNode()`
+fn remove_node<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  if let Some(this) = this_node(ctx) {
+    detach(&this);
+  }
+  Ok(AvmValue::UNDEFINED)
+}
+
+/// `XMLNode.prototype.hasChildNodes()`
+fn has_child_nodes<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let result = this_node(ctx).is_some_and(|this| !children(&this).is_empty());
+  Ok(AvmValue::boolean(result))
+}
+
+/// `XMLNode.prototype.cloneNode(deep)`, the clone has no parent.
+fn clone_node<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let this = match this_node(ctx) {
+    Some(this) => this,
+    None => return Ok(AvmValue::UNDEFINED),
+  };
+  let deep = ctx.arg(0).to_avm_boolean().value();
+  Ok(AvmValue::Object(clone_tree(ctx, &this, deep)))
+}
+
+/// Clones `node` and, if `deep` is set, its descendants.
+///
+/// The tree is walked without recursion, so deeply nested documents do not overflow the stack.
+fn clone_tree<'gc>(ctx: &mut dyn CallContext<'gc>, node: &AvmObjectRef<'gc>, deep: bool) -> AvmObjectRef<'gc> {
+  let clone = clone_single_node(ctx, node);
+  if !deep {
+    return clone;
+  }
+  // Clones whose children are being cloned, with the remaining source children and the clones
+  // of the previous ones
+  let mut open: Vec<(AvmObjectRef<'gc>, ::std::vec::IntoIter<AvmObjectRef<'gc>>, Vec<AvmObjectRef<'gc>>)> =
+    vec![(clone, children(node).into_iter(), Vec::new())];
+  loop {
+    let next = open.last_mut().unwrap().1.next();
+    match next {
+      Some(child) => {
+        let child_clone = clone_single_node(ctx, &child);
+        open.push((child_clone, children(&child).into_iter(), Vec::new()));
+      }
+      None => {
+        let (clone, _, cloned_children) = open.pop().unwrap();
+        set_children(&clone, cloned_children);
+        match open.last_mut() {
+          Some(parent) => parent.2.push(clone),
+          None => return clone,
+        }
+      }
+    }
+  }
+}
+
+/// Clones `node` and its attributes, without its children.
+fn clone_single_node<'gc>(ctx: &mut dyn CallContext<'gc>, node: &AvmObjectRef<'gc>) -> AvmObjectRef<'gc> {
+  let node_type = get(node, "nodeType").to_avm_number().value();
+  let clone = new_node(ctx, node_type, get(node, "nodeName"), get(node, "nodeValue"));
+  if let (AvmValue::Object(attributes), AvmValue::Object(clone_attributes)) = (get(node, "attributes"), get(&clone, "attributes")) {
+    let properties = attributes.0.borrow().properties.clone();
+    clone_attributes.0.borrow_mut().properties = properties;
+  }
+  clone
+}
+
+/// `XMLNode.prototype.toString()`, serializes the node and its descendants.
+fn to_string<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let this = match this_node(ctx) {
+    Some(this) => this,
+    None => return Ok(AvmValue::UNDEFINED),
+  };
+  let mut result = String::new();
+  if this.0.borrow().class == AvmObjectClass::XML {
+    for key in &["xmlDecl", "docTypeDecl"] {
+      match get(&this, key) {
+        AvmValue::Undefined(_) | AvmValue::Null(_) => {}
+        decl => result.push_str(decl.to_avm_string(ctx)?.value()),
+      }
+    }
+  }
+  write_node(ctx, &this, &mut result)?;
+  Ok(AvmValue::String(ctx.string(result).unwrap()))
+}
+
+/// Escapes the XML special characters of a text node or an attribute value.
+fn escape(value: &str) -> String {
+  let mut result = String::with_capacity(value.len());
+  for c in value.chars() {
+    match c {
+      '&' => result.push_str("&amp;"),
+      '<' => result.push_str("&lt;"),
+      '>' => result.push_str("&gt;"),
+      '"' => result.push_str("&quot;"),
+      '\'' => result.push_str("&apos;"),
+      c => result.push(c),
+    }
+  }
+  result
+}
+
+/// Step of `write_node`: a node to serialize, or the end tag of an element
+enum WriteStep<'gc> {
+  Node(AvmObjectRef<'gc>),
+  EndTag(String),
+}
+
+/// Serializes `node` and its descendants, without recursion so deeply nested documents do not
+/// overflow the stack.
+///
+/// Attributes are written in the order they were added, which is the source order for parsed
+/// elements.
+fn write_node<'gc>(ctx: &mut dyn CallContext<'gc>, node: &AvmObjectRef<'gc>, out: &mut String) -> Result<(), AvmValue<'gc>> {
+  let mut steps: Vec<WriteStep<'gc>> = vec![WriteStep::Node(node.clone())];
+  while let Some(step) = steps.pop() {
+    let node = match step {
+      WriteStep::Node(node) => node,
+      WriteStep::EndTag(name) => {
+        out.push_str(&format!("</{}>", name));
+        continue;
+      }
+    };
+    if get(&node, "nodeType").to_avm_number().value() == TEXT_NODE {
+      let value = get(&node, "nodeValue").to_avm_string(ctx)?;
+      out.push_str(&escape(value.value()));
+      continue;
+    }
+    let name = match get(&node, "nodeName") {
+      AvmValue::Undefined(_) | AvmValue::Null(_) => None,
+      name => Some(name.to_avm_string(ctx)?.value().to_string()),
+    };
+    let children = children(&node);
+    if let Some(name) = name {
+      out.push('<');
+      out.push_str(&name);
+      if let AvmValue::Object(attributes) = get(&node, "attributes") {
+        let keys: Vec<String> = attributes.0.borrow().properties
+          .iter()
+          .filter(|(_, property)| property.enumerable && property.is_visible(ctx.swf_version()))
+          .map(|(key, _)| key.clone())
+          .collect();
+        for key in keys {
+          let value = get(&attributes, &key).to_avm_string(ctx)?;
+          out.push_str(&format!(" {}=\"{}\"", key, escape(value.value())));
+        }
+      }
+      if children.is_empty() {
+        out.push_str(" />");
+        continue;
+      }
+      out.push('>');
+      steps.push(WriteStep::EndTag(name));
+    }
+    steps.extend(children.into_iter().rev().map(WriteStep::Node));
+  }
+  Ok(())
+}
+
+/// `XML.prototype.createElement(name)`
+fn create_element<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let name = ctx.arg(0).to_avm_string(ctx)?;
+  Ok(AvmValue::Object(new_node(ctx, ELEMENT_NODE, AvmValue::String(name), AvmValue::NULL)))
+}
+
+/// `XML.prototype.createTextNode(value)`
+fn create_text_node<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let value = ctx.arg(0).to_avm_string(ctx)?;
+  Ok(AvmValue::Object(new_node(ctx, TEXT_NODE, AvmValue::NULL, AvmValue::String(value))))
+}
+
+/// `XML.prototype.parseXML(source)`, replaces the content of the document.
+fn parse_xml<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  if let Some(this) = this_node(ctx) {
+    let source = string_arg(ctx, 0)?;
+    parse_into(ctx, &this, &source);
+  }
+  Ok(AvmValue::UNDEFINED)
+}
+
+/// Parses `source` into the document `doc`, setting `status`, `xmlDecl` and `docTypeDecl`.
+///
+/// The nodes read before a parse error are kept.
+fn parse_into<'gc>(ctx: &mut dyn CallContext<'gc>, doc: &AvmObjectRef<'gc>, source: &str) -> () {
+  let ignore_white = get(doc, "ignoreWhite").to_avm_boolean().value();
+  let ParseResult { nodes, xml_decl, doc_type_decl, status } = parser::parse(source, ignore_white);
+  for child in children(doc) {
+    clear_links(&child);
+  }
+  let doc_children = build_nodes(ctx, nodes);
+  set_children(doc, doc_children);
+  let xml_decl = xml_decl.map_or(AvmValue::UNDEFINED, |decl| AvmValue::string(ctx.gc(), decl).unwrap());
+  let doc_type_decl = doc_type_decl.map_or(AvmValue::UNDEFINED, |decl| AvmValue::string(ctx.gc(), decl).unwrap());
+  let mut doc = doc.0.borrow_mut();
+  doc.set(String::from("xmlDecl"), xml_decl);
+  doc.set(String::from("docTypeDecl"), doc_type_decl);
+  doc.set(String::from("status"), AvmValue::number(f64::from(status)));
+}
+
+/// Creates the nodes of the parsed trees `parsed`.
+///
+/// The trees are walked without recursion, so deeply nested documents do not overflow the stack.
+fn build_nodes<'gc>(ctx: &mut dyn CallContext<'gc>, parsed: Vec<ParsedNode>) -> Vec<AvmObjectRef<'gc>> {
+  // Elements whose children are being created (`None` for the top level), with the remaining
+  // parsed children and the nodes created for the previous ones
+  let mut open: Vec<(Option<AvmObjectRef<'gc>>, ::std::vec::IntoIter<ParsedNode>, Vec<AvmObjectRef<'gc>>)> =
+    vec![(None, parsed.into_iter(), Vec::new())];
+  loop {
+    let next = open.last_mut().unwrap().1.next();
+    match next {
+      Some(ParsedNode::Text(value)) => {
+        let value = AvmValue::string(ctx.gc(), value).unwrap();
+        let node = new_node(ctx, TEXT_NODE, AvmValue::NULL, value);
+        open.last_mut().unwrap().2.push(node);
+      }
+      Some(ParsedNode::Element { name, attributes, children }) => {
+        let name = AvmValue::string(ctx.gc(), name).unwrap();
+        let node = new_node(ctx, ELEMENT_NODE, name, AvmValue::NULL);
+        if let AvmValue::Object(node_attributes) = get(&node, "attributes") {
+          for (key, value) in attributes {
+            let value = AvmValue::string(ctx.gc(), value).unwrap();
+            node_attributes.0.borrow_mut().set(key, value);
+          }
+        }
+        open.push((Some(node), children.into_iter(), Vec::new()));
+      }
+      None => {
+        let (node, _, node_children) = open.pop().unwrap();
+        let node = match node {
+          Some(node) => node,
+          None => return node_children,
+        };
+        set_children(&node, node_children);
+        open.last_mut().unwrap().2.push(node);
+      }
+    }
+  }
+}
+
+/// Passes the loaded source (or `undefined` on failure) to `target.onData`.
+fn deliver<'gc>(ctx: &mut dyn CallContext<'gc>, target: &AvmObjectRef<'gc>, source: Option<String>) -> AvmResult<'gc> {
+  target.0.borrow_mut().set(String::from("loaded"), AvmValue::FALSE);
+  let source = source.map_or(AvmValue::UNDEFINED, |source| AvmValue::string(ctx.gc(), source).unwrap());
+  match get(target, "onData") {
+    on_data @ AvmValue::Object(_) => ctx.apply(on_data, AvmValue::Object(target.clone()), &[source]),
+    _ => Ok(AvmValue::UNDEFINED),
+  }
+}
+
+/// `XML.prototype.load(url)`
+///
+/// The host loads the document synchronously: `onData` and `onLoad` run before `load` returns.
+fn load<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let this = match this_node(ctx) {
+    Some(this) => this,
+    None => return Ok(AvmValue::FALSE),
+  };
+  let url = string_arg(ctx, 0)?;
+  let source = ctx.host().load_url(&url, None);
+  deliver(ctx, &this, source)?;
+  Ok(AvmValue::TRUE)
+}
+
+/// `XML.prototype.sendAndLoad(url, target)`, sends the document and loads the response into `target`.
+fn send_and_load<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let (this, target) = match (this_node(ctx), as_node(&ctx.arg(1))) {
+    (Some(this), Some(target)) => (this, target),
+    _ => return Ok(AvmValue::FALSE),
+  };
+  let url = string_arg(ctx, 0)?;
+  let data = match ctx.apply(get(&this, "toString"), AvmValue::Object(this.clone()), &[])? {
+    AvmValue::String(data) => data.value().to_string(),
+    _ => String::new(),
+  };
+  let source = ctx.host().load_url(&url, Some(&data));
+  deliver(ctx, &target, source)?;
+  Ok(AvmValue::TRUE)
+}
+
+/// `XML.prototype.onData(source)`: parses the source, then calls `onLoad(success)`.
+fn on_data<'gc>(ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  let this = match this_node(ctx) {
+    Some(this) => this,
+    None => return Ok(AvmValue::UNDEFINED),
+  };
+  let success = match ctx.arg(0) {
+    AvmValue::Undefined(_) => false,
+    source => {
+      let source = source.to_avm_string(ctx)?.value().to_string();
+      parse_into(ctx, &this, &source);
+      true
+    }
+  };
+  this.0.borrow_mut().set(String::from("loaded"), AvmValue::boolean(success));
+  match get(&this, "onLoad") {
+    on_load @ AvmValue::Object(_) => ctx.apply(on_load, AvmValue::Object(this), &[AvmValue::boolean(success)]),
+    _ => Ok(AvmValue::UNDEFINED),
+  }
+}
+
+/// `XML.prototype.onLoad(success)`, does nothing until overridden.
+fn on_load<'gc>(_ctx: &mut dyn CallContext<'gc>) -> AvmResult<'gc> {
+  Ok(AvmValue::UNDEFINED)
+}
+
+/// Lenient XML parser, following the Flash Player behavior.
+mod parser {
+  /// Values of `XML.status`
+  const STATUS_OK: i32 = 0;
+  const STATUS_CDATA_NOT_TERMINATED: i32 = -2;
+  const STATUS_XML_DECL_NOT_TERMINATED: i32 = -3;
+  const STATUS_DOCTYPE_NOT_TERMINATED: i32 = -4;
+  const STATUS_COMMENT_NOT_TERMINATED: i32 = -5;
+  const STATUS_MALFORMED_ELEMENT: i32 = -6;
+  const STATUS_ATTRIBUTE_NOT_TERMINATED: i32 = -8;
+  const STATUS_MISSING_END_TAG: i32 = -9;
+  const STATUS_MISSING_START_TAG: i32 = -10;
+
+  #[derive(Debug, Clone, PartialEq)]
+  pub(super) enum ParsedNode {
+    Element {
+      name: String,
+      attributes: Vec<(String, String)>,
+      children: Vec<ParsedNode>,
+    },
+    Text(String),
+  }
+
+  #[derive(Debug)]
+  pub(super) struct ParseResult {
+    pub(super) nodes: Vec<ParsedNode>,
+    pub(super) xml_decl: Option<String>,
+    pub(super) doc_type_decl: Option<String>,
+    pub(super) status: i32,
+  }
+
+  /// Element whose end tag was not read yet
+  struct OpenElement {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<ParsedNode>,
+  }
+
+  /// Parses `source`, stopping at the first error.
+  ///
+  /// Elements still open at the end (or when an error occurs) are closed implicitly. With
+  /// `ignore_white`, text nodes containing only whitespace are dropped. Comments are dropped and
+  /// CDATA sections become text nodes.
+  pub(super) fn parse(source: &str, ignore_white: bool) -> ParseResult {
+    let mut nodes: Vec<ParsedNode> = Vec::new();
+    let mut stack: Vec<OpenElement> = Vec::new();
+    let mut xml_decl: Option<String> = None;
+    let mut doc_type_decl: Option<String> = None;
+    let mut status = STATUS_OK;
+    let mut rest: &str = source;
+
+    fn push_node(nodes: &mut Vec<ParsedNode>, stack: &mut [OpenElement], node: ParsedNode) -> () {
+      match stack.last_mut() {
+        Some(parent) => parent.children.push(node),
+        None => nodes.push(node),
+      }
+    }
+
+    while !rest.is_empty() {
+      if rest.starts_with("<!--") {
+        match rest.find("-->") {
+          Some(end) => rest = &rest[end + 3..],
+          None => {
+            status = STATUS_COMMENT_NOT_TERMINATED;
+            break;
+          }
+        }
+      } else if rest.starts_with("<![CDATA[") {
+        match rest.find("]]>") {
+          Some(end) => {
+            push_node(&mut nodes, &mut stack, ParsedNode::Text(rest[9..end].to_string()));
+            rest = &rest[end + 3..];
+          }
+          None => {
+            status = STATUS_CDATA_NOT_TERMINATED;
+            break;
+          }
+        }
+      } else if rest.starts_with("<?") {
+        match rest.find("?>") {
+          Some(end) => {
+            xml_decl.get_or_insert_with(String::new).push_str(&rest[..end + 2]);
+            rest = &rest[end + 2..];
+          }
+          None => {
+            status = STATUS_XML_DECL_NOT_TERMINATED;
+            break;
+          }
+        }
+      } else if rest.starts_with("<!") {
+        match rest.find('>') {
+          Some(end) => {
+            doc_type_decl = Some(rest[..end + 1].to_string());
+            rest = &rest[end + 1..];
+          }
+          None => {
+            status = STATUS_DOCTYPE_NOT_TERMINATED;
+            break;
+          }
+        }
+      } else if rest.starts_with("</") {
+        let end = match rest.find('>') {
+          Some(end) => end,
+          None => {
+            status = STATUS_MALFORMED_ELEMENT;
+            break;
+          }
+        };
+        let name = rest[2..end].trim();
+        rest = &rest[end + 1..];
+        match stack.last() {
+          Some(open) if open.name == name => {
+            let open = stack.pop().unwrap();
+            let element = ParsedNode::Element { name: open.name, attributes: open.attributes, children: open.children };
+            push_node(&mut nodes, &mut stack, element);
+          }
+          _ => {
+            status = if stack.iter().any(|open| open.name == name) {
+              STATUS_MISSING_END_TAG
+            } else {
+              STATUS_MISSING_START_TAG
+            };
+            break;
+          }
+        }
+      } else if rest.starts_with('<') {
+        match parse_start_tag(&rest[1..]) {
+          Ok((element, self_closing, remaining)) => {
+            rest = remaining;
+            if self_closing {
+              let element = ParsedNode::Element { name: element.name, attributes: element.attributes, children: Vec::new() };
+              push_node(&mut nodes, &mut stack, element);
+            } else {
+              stack.push(element);
+            }
+          }
+          Err(error) => {
+            status = error;
+            break;
+          }
+        }
+      } else {
+        let end = rest.find('<').unwrap_or(rest.len());
+        let text = &rest[..end];
+        rest = &rest[end..];
+        if !(ignore_white && text.chars().all(char::is_whitespace)) {
+          push_node(&mut nodes, &mut stack, ParsedNode::Text(decode_entities(text)));
+        }
+      }
+    }
+
+    if !stack.is_empty() && status == STATUS_OK {
+      status = STATUS_MISSING_END_TAG;
+    }
+    while let Some(open) = stack.pop() {
+      let element = ParsedNode::Element { name: open.name, attributes: open.attributes, children: open.children };
+      push_node(&mut nodes, &mut stack, element);
+    }
+
+    ParseResult { nodes, xml_decl, doc_type_decl, status }
+  }
+
+  /// Parses a start tag following `<`, returns the element, whether it is self-closing (`<a />`),
+  /// and the remaining input.
+  fn parse_start_tag(input: &str) -> Result<(OpenElement, bool, &str), i32> {
+    let is_name_end = |c: char| c.is_whitespace() || c == '/' || c == '>';
+    let name_end = input.find(is_name_end).ok_or(STATUS_MALFORMED_ELEMENT)?;
+    if name_end == 0 {
+      return Err(STATUS_MALFORMED_ELEMENT);
+    }
+    let name = input[..name_end].to_string();
+    let mut attributes: Vec<(String, String)> = Vec::new();
+    let mut rest = &input[name_end..];
+    loop {
+      rest = rest.trim_start();
+      if let Some(after) = rest.strip_prefix("/>") {
+        return Ok((OpenElement { name, attributes, children: Vec::new() }, true, after));
+      }
+      if let Some(after) = rest.strip_prefix('>') {
+        return Ok((OpenElement { name, attributes, children: Vec::new() }, false, after));
+      }
+      let key_end = rest
+        .find(|c: char| c.is_whitespace() || c == '=' || c == '/' || c == '>')
+        .ok_or(STATUS_MALFORMED_ELEMENT)?;
+      if key_end == 0 {
+        return Err(STATUS_MALFORMED_ELEMENT);
+      }
+      let key = rest[..key_end].to_string();
+      rest = rest[key_end..].trim_start();
+      if !rest.starts_with('=') {
+        return Err(STATUS_MALFORMED_ELEMENT);
+      }
+      rest = rest[1..].trim_start();
+      let quote = match rest.chars().next() {
+        Some(quote @ '"') | Some(quote @ '\'') => quote,
+        _ => return Err(STATUS_MALFORMED_ELEMENT),
+      };
+      let value_end = rest[1..].find(quote).ok_or(STATUS_ATTRIBUTE_NOT_TERMINATED)?;
+      let value = decode_entities(&rest[1..1 + value_end]);
+      rest = &rest[value_end + 2..];
+      // A repeated attribute overrides the previous value
+      attributes.retain(|(k, _)| *k != key);
+      attributes.push((key, value));
+    }
+  }
+
+  /// Decodes the predefined entities and the character references, unknown entities are kept.
+  fn decode_entities(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+      result.push_str(&rest[..start]);
+      rest = &rest[start..];
+      let decoded = rest.find(';').and_then(|end| {
+        let decoded = match &rest[1..end] {
+          "lt" => Some('<'),
+          "gt" => Some('>'),
+          "amp" => Some('&'),
+          "quot" => Some('"'),
+          "apos" => Some('\''),
+          entity if entity.starts_with("#x") || entity.starts_with("#X") => {
+            u32::from_str_radix(&entity[2..], 16).ok().and_then(::std::char::from_u32)
+          }
+          entity if entity.starts_with('#') => entity[1..].parse::<u32>().ok().and_then(::std::char::from_u32),
+          _ => None,
+        };
+        decoded.map(|c| (c, end))
+      });
+      match decoded {
+        Some((c, end)) => {
+          result.push(c);
+          rest = &rest[end + 1..];
+        }
+        None => {
+          result.push('&');
+          rest = &rest[1..];
+        }
+      }
+    }
+    result.push_str(rest);
+    result
+  }
+}
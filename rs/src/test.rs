@@ -1,9 +1,10 @@
 use ::scoped_gc::GcScope;
 use avm1_tree;
 
-use crate::avm1::Vm;
-use crate::host::LoggedHost;
-use crate::values::{AvmString, AvmValue};
+use crate::avm1::{TargetId, Vm, DEFAULT_MAX_PROTOTYPE_DEPTH};
+use crate::error::Warning;
+use crate::host::{FrameScript, Host, LoggedHost, XorShift64, LOGGED_HOST_SEED};
+use crate::values::{number_to_string, AvmNumber, AvmObject, AvmString, AvmValue};
 use ::test_generator::test_expand_paths;
 
 #[test]
@@ -17,6 +18,71 @@ fn avm_value_eq() {
   assert_eq!(foo, AvmValue::String(AvmString::new(&gc_scope, String::from("Hello, World!")).unwrap()));
 }
 
+#[test]
+fn avm_value_same_value() {
+  let nan_payload = f64::from_bits(f64::NAN.to_bits() | 1);
+  assert_eq!(AvmNumber::new(nan_payload).value().to_bits(), f64::NAN.to_bits());
+  assert!(AvmValue::number(f64::NAN).same_value(&AvmValue::number(nan_payload)));
+  assert!(!AvmValue::number(0f64).same_value(&AvmValue::number(-0f64)));
+  assert_eq!(1f64 / AvmNumber::new(-0f64).value(), f64::NEG_INFINITY);
+  assert!(!AvmValue::number(1f64).same_value(&AvmValue::TRUE));
+  assert!(AvmValue::NULL.same_value(&AvmValue::NULL));
+  assert_eq!(AvmValue::number(1f64), AvmValue::number(1f64));
+  assert_eq!(AvmValue::NAN, AvmValue::number(nan_payload));
+  assert_ne!(AvmValue::ZERO, AvmValue::number(-0f64));
+}
+
+#[test]
+fn prototype_chain_lookup() {
+  let gc = GcScope::new();
+  let base = AvmObject::new(&gc, None).unwrap();
+  base.0.borrow_mut().set(String::from("inherited"), AvmValue::ONE);
+  let middle = AvmObject::new(&gc, Some(base.clone())).unwrap();
+  let obj = AvmObject::new(&gc, Some(middle.clone())).unwrap();
+  obj.0.borrow_mut().set(String::from("own"), AvmValue::ZERO);
+
+  assert!(obj.0.borrow().get("inherited").unwrap().same_value(&AvmValue::ONE));
+  assert!(obj.0.borrow().has_property("inherited", 11, DEFAULT_MAX_PROTOTYPE_DEPTH));
+  assert!(!obj.0.borrow().has_own_property("inherited", 11));
+  let keys = obj.0.borrow().enumerable_keys(11, DEFAULT_MAX_PROTOTYPE_DEPTH);
+  assert_eq!(keys, vec![String::from("own"), String::from("inherited")]);
+
+  // Prototypes past the maximum depth are ignored
+  assert_eq!(obj.0.borrow().prototypes(1).len(), 1);
+  assert!(!obj.0.borrow().has_property("inherited", 11, 1));
+  assert!(obj.0.borrow().get_versioned("inherited", 11, 1).is_none());
+
+  // Cycles are tolerated
+  base.0.borrow_mut().set(String::from("__proto__"), AvmValue::Object(obj.clone()));
+  assert!(obj.0.borrow().get("missing").is_none());
+  assert_eq!(obj.0.borrow().prototypes(DEFAULT_MAX_PROTOTYPE_DEPTH).len(), 2);
+}
+
+#[test]
+fn as_set_prop_flags() {
+  let gc = GcScope::new();
+  let host = LoggedHost::new();
+  let mut vm = Vm::new(&gc, &host, 6);
+  let obj = AvmObject::new(&gc, None).unwrap();
+  obj.0.borrow_mut().set(String::from("a"), AvmValue::ONE);
+  obj.0.borrow_mut().set(String::from("b"), AvmValue::ONE);
+
+  // Hide `a` from enumeration and make it read-only, only hide `b` below SWF7
+  let props = AvmValue::string(&gc, String::from("a, b")).unwrap();
+  let as_set_prop_flags = AvmValue::Object(vm.realm.as_set_prop_flags.clone());
+  let args = [AvmValue::Object(obj.clone()), props, AvmValue::number(5f64), AvmValue::ZERO];
+  vm.apply(as_set_prop_flags.clone(), AvmValue::UNDEFINED, &args).unwrap();
+  let props = AvmValue::string(&gc, String::from("b")).unwrap();
+  let args = [AvmValue::Object(obj.clone()), props, AvmValue::number(1024f64), AvmValue::number(5f64)];
+  vm.apply(as_set_prop_flags, AvmValue::UNDEFINED, &args).unwrap();
+
+  obj.0.borrow_mut().set(String::from("a"), AvmValue::ZERO);
+  assert!(obj.0.borrow().get("a").unwrap().same_value(&AvmValue::ONE));
+  assert_eq!(obj.0.borrow().enumerable_keys(7, DEFAULT_MAX_PROTOTYPE_DEPTH), vec![String::from("b")]);
+  assert!(obj.0.borrow().get_versioned("b", 6, DEFAULT_MAX_PROTOTYPE_DEPTH).is_none());
+  assert!(obj.0.borrow_mut().delete("a"));
+}
+
 test_expand_paths! { test_avm1; "../tests/avm1/*/*/" }
 fn test_avm1(path: &str) {
   use std::path::Path;
@@ -34,12 +100,22 @@ fn test_avm1(path: &str) {
     "template" => return, // Internal
     "constant-on-stack-definition" => return, // Requires uninitialized constant pool
     "constant-without-pool" => return, // Requires uninitialized constant pool
-    "corrupted-push" => return, // Requires parser support for corrupted data
     _ => (),
   }
 
-  let avm1_path = path.join("main.avm1");
-  let log_path = path.join("main.log");
+  run_sample(&path.join("main.avm1"), &path.join("main.log"));
+}
+
+test_expand_paths! { test_local_avm1; "../tests/*.avm1" }
+fn test_local_avm1(path: &str) {
+  use std::path::Path;
+
+  let avm1_path: &Path = Path::new(path);
+  run_sample(avm1_path, &avm1_path.with_extension("log"));
+}
+
+/// Runs the AVM1 file at `avm1_path` and checks that the host logs match the content of `log_path`.
+fn run_sample(avm1_path: &::std::path::Path, log_path: &::std::path::Path) -> () {
   let avm1_bytes: Vec<u8> = ::std::fs::read(avm1_path).expect("Failed to read AVM1 file");
   let expected_logs: String = ::std::fs::read_to_string(log_path).expect("Failed to read log");
 
@@ -54,6 +130,539 @@ fn test_avm1(path: &str) {
   assert_eq!(actual_logs, expected_logs);
 }
 
+#[test]
+fn number_to_string_uses_flash_format() {
+  let cases: Vec<(f64, &str)> = vec![
+    (0.1 + 0.2, "0.3"),
+    (-0f64, "0"),
+    (1e15, "1e+15"),
+    (123456789012345f64, "123456789012345"),
+    (0.0001, "0.0001"),
+    (0.00001, "1e-5"),
+    (-1.5e-7, "-1.5e-7"),
+    (f64::INFINITY, "Infinity"),
+  ];
+  for (value, expected) in cases {
+    assert_eq!(number_to_string(value), expected);
+  }
+  assert_eq!(AvmNumber::new(4294967301f64).to_int32(), 5);
+  assert_eq!(AvmNumber::new(-3.9f64).to_int32(), -3);
+}
+
+// Not a sample: the samples always run as SWF 11
+#[test]
+fn arithmetic_conversions_depend_on_swf_version() {
+  let mut avm1_bytes: Vec<u8> = Vec::new();
+  avm1_bytes.extend_from_slice(&[0x96, 0x01, 0x00, 0x03]); // Push undefined
+  push_i32(&mut avm1_bytes, 1);
+  avm1_bytes.push(0x0a); // Add
+  avm1_bytes.push(0x26); // Trace
+  push_string(&mut avm1_bytes, "4");
+  avm1_bytes.push(0x50); // Increment
+  avm1_bytes.push(0x26); // Trace
+  avm1_bytes.push(0x00);
+
+  let cases: [(u8, [&str; 2]); 3] = [(4, ["1", "1"]), (6, ["1", "5"]), (7, ["NaN", "5"])];
+  for (swf_version, expected) in cases.iter() {
+    let host = LoggedHost::new();
+    let gc = GcScope::new();
+    let mut vm = Vm::new(&gc, &host, *swf_version);
+    let script_id = vm.create_script(avm1_bytes.clone(), None, None);
+    vm.run_to_completion(script_id);
+    assert_eq!(*host.logs.borrow(), expected.to_vec(), "SWF version {}", swf_version);
+  }
+}
+
+// Not a sample: the samples always run as SWF 11
+#[test]
+fn global_variables_depend_on_swf_version() {
+  let host = LoggedHost::new();
+  let gc = GcScope::new();
+  let mut vm = Vm::new(&gc, &host, 6);
+  let global = vm.realm.global.clone();
+  global.0.borrow_mut().set(String::from("v"), AvmValue::ONE);
+  // Hide `_global.v` below SWF7
+  let props = AvmValue::string(&gc, String::from("v")).unwrap();
+  let as_set_prop_flags = AvmValue::Object(vm.realm.as_set_prop_flags.clone());
+  let args = [AvmValue::Object(global.clone()), props, AvmValue::number(1024f64), AvmValue::ZERO];
+  vm.apply(as_set_prop_flags, AvmValue::UNDEFINED, &args).unwrap();
+
+  let mut avm1_bytes: Vec<u8> = Vec::new();
+  push_string(&mut avm1_bytes, "v");
+  avm1_bytes.push(0x1c); // GetVariable
+  avm1_bytes.push(0x26); // Trace
+  push_string(&mut avm1_bytes, "v");
+  push_string(&mut avm1_bytes, "timeline");
+  avm1_bytes.push(0x1d); // SetVariable
+  push_string(&mut avm1_bytes, "v");
+  avm1_bytes.push(0x1c); // GetVariable
+  avm1_bytes.push(0x26); // Trace
+  avm1_bytes.push(0x00);
+  let script_id = vm.create_script(avm1_bytes, None, None);
+  vm.run_to_completion(script_id);
+
+  let expected = ["Warning: Reference to undeclared variable, 'v'", "undefined", "timeline"];
+  assert_eq!(*host.logs.borrow(), expected.to_vec());
+  assert!(global.0.borrow().get("v").unwrap().same_value(&AvmValue::ONE));
+}
+
+// Not a sample: the scripts run on two timelines of the same VM
+#[test]
+fn global_object_is_shared_by_timelines() {
+  let host = LoggedHost::new();
+  let gc = GcScope::new();
+  let mut vm = Vm::new(&gc, &host, 11);
+
+  // _global.g = "shared"; x = "local";
+  let mut first: Vec<u8> = Vec::new();
+  push_string(&mut first, "_global.g");
+  push_string(&mut first, "shared");
+  first.push(0x1d); // SetVariable
+  push_string(&mut first, "x");
+  push_string(&mut first, "local");
+  first.push(0x1d); // SetVariable
+  first.push(0x00);
+  let first = vm.create_script(first, None, Some(TargetId(1)));
+  vm.run_to_completion(first);
+
+  // trace(g); trace(x); g = "updated"; trace(_global.g);
+  let mut second: Vec<u8> = Vec::new();
+  for name in &["g", "x"] {
+    push_string(&mut second, name);
+    second.push(0x1c); // GetVariable
+    second.push(0x26); // Trace
+  }
+  push_string(&mut second, "g");
+  push_string(&mut second, "updated");
+  second.push(0x1d); // SetVariable
+  push_string(&mut second, "_global.g");
+  second.push(0x1c); // GetVariable
+  second.push(0x26); // Trace
+  second.push(0x00);
+  let second = vm.create_script(second, None, Some(TargetId(2)));
+  vm.run_to_completion(second);
+
+  let expected: Vec<String> = vec![
+    "shared", "Warning: Reference to undeclared variable, 'x'", "undefined", "updated",
+  ].into_iter().map(String::from).collect();
+  assert_eq!(*host.logs.borrow(), expected);
+}
+
+// Not a sample: the date and timezone are set on the host
+#[test]
+fn date_uses_host_clock_and_timezone() {
+  let host = LoggedHost::new();
+  // 2005-02-01T00:00:00Z, in UTC-08:00
+  host.set_date(1107216000000f64);
+  host.set_timezone_offset(480f64);
+
+  let mut avm1_bytes: Vec<u8> = Vec::new();
+  push_string(&mut avm1_bytes, "d");
+  push_i32(&mut avm1_bytes, 0);
+  push_string(&mut avm1_bytes, "Date");
+  avm1_bytes.push(0x40); // NewObject
+  avm1_bytes.push(0x1d); // SetVariable
+  for method in &["toString", "getFullYear", "getUTCFullYear", "getUTCDate", "getTimezoneOffset"] {
+    call_method(&mut avm1_bytes, "d", method, 0);
+    avm1_bytes.push(0x26); // Trace
+  }
+  // d.setUTCHours(12, 30); trace(d.getUTCMinutes()); trace(d + 1);
+  push_i32(&mut avm1_bytes, 30);
+  push_i32(&mut avm1_bytes, 12);
+  call_method(&mut avm1_bytes, "d", "setUTCHours", 2);
+  avm1_bytes.push(0x17); // Pop
+  call_method(&mut avm1_bytes, "d", "getUTCMinutes", 0);
+  avm1_bytes.push(0x26); // Trace
+  push_string(&mut avm1_bytes, "d");
+  avm1_bytes.push(0x1c); // GetVariable
+  push_i32(&mut avm1_bytes, 1);
+  avm1_bytes.push(0x47); // Add2
+  avm1_bytes.push(0x26); // Trace
+  // trace(Date.UTC(2005, 1, 1));
+  push_i32(&mut avm1_bytes, 1);
+  push_i32(&mut avm1_bytes, 1);
+  push_i32(&mut avm1_bytes, 2005);
+  call_method(&mut avm1_bytes, "Date", "UTC", 3);
+  avm1_bytes.push(0x26); // Trace
+  avm1_bytes.push(0x00);
+
+  let expected: Vec<String> = vec![
+    "Mon Jan 31 16:00:00 GMT-0800 2005", "2005", "2005", "1", "480", "30", "1107261000001", "1107216000000",
+  ].into_iter().map(String::from).collect();
+  assert_eq!(run_avm1_bytes_with_host(&host, avm1_bytes), expected);
+}
+
+// Not a sample: timers only run when the host advances the time
+#[test]
+fn timers_fire_when_time_advances() {
+  let host = LoggedHost::new();
+  let gc = GcScope::new();
+  let mut vm = Vm::new(&gc, &host, 11);
+
+  let mut avm1_bytes: Vec<u8> = Vec::new();
+  // f = function (a) { trace(a); }; o = {m: f};
+  push_string(&mut avm1_bytes, "f");
+  let mut body: Vec<u8> = Vec::new();
+  push_string(&mut body, "a");
+  body.push(0x1c); // GetVariable
+  body.push(0x26); // Trace
+  define_function(&mut avm1_bytes, &["a"], &body);
+  avm1_bytes.push(0x1d); // SetVariable
+  push_string(&mut avm1_bytes, "o");
+  push_string(&mut avm1_bytes, "m");
+  push_string(&mut avm1_bytes, "f");
+  avm1_bytes.push(0x1c); // GetVariable
+  push_i32(&mut avm1_bytes, 1);
+  avm1_bytes.push(0x43); // InitObject
+  avm1_bytes.push(0x1d); // SetVariable
+  // id = setInterval(f, 100, "tick"); setTimeout(f, 100, "same time"); setTimeout(o, "m", 150, "method");
+  push_string(&mut avm1_bytes, "id");
+  push_string(&mut avm1_bytes, "tick");
+  push_i32(&mut avm1_bytes, 100);
+  push_string(&mut avm1_bytes, "f");
+  avm1_bytes.push(0x1c); // GetVariable
+  push_i32(&mut avm1_bytes, 3);
+  push_string(&mut avm1_bytes, "setInterval");
+  avm1_bytes.push(0x3d); // CallFunction
+  avm1_bytes.push(0x1d); // SetVariable
+  push_string(&mut avm1_bytes, "same time");
+  push_i32(&mut avm1_bytes, 100);
+  push_string(&mut avm1_bytes, "f");
+  avm1_bytes.push(0x1c); // GetVariable
+  push_i32(&mut avm1_bytes, 3);
+  push_string(&mut avm1_bytes, "setTimeout");
+  avm1_bytes.push(0x3d); // CallFunction
+  avm1_bytes.push(0x17); // Pop
+  push_string(&mut avm1_bytes, "method");
+  push_i32(&mut avm1_bytes, 150);
+  push_string(&mut avm1_bytes, "m");
+  push_string(&mut avm1_bytes, "o");
+  avm1_bytes.push(0x1c); // GetVariable
+  push_i32(&mut avm1_bytes, 4);
+  push_string(&mut avm1_bytes, "setTimeout");
+  avm1_bytes.push(0x3d); // CallFunction
+  avm1_bytes.push(0x17); // Pop
+  avm1_bytes.push(0x00);
+  let script = vm.create_script(avm1_bytes, None, None);
+  vm.run_to_completion(script);
+
+  vm.advance_time(99f64);
+  assert!(host.logs.borrow().is_empty());
+  // Invalid durations do not move the clock
+  vm.advance_time(f64::NAN);
+  vm.advance_time(-50f64);
+  vm.advance_time(f64::INFINITY);
+  assert!(host.logs.borrow().is_empty());
+  vm.advance_time(51f64);
+  vm.advance_time(50f64);
+
+  // clearInterval(id);
+  let mut avm1_bytes: Vec<u8> = Vec::new();
+  push_string(&mut avm1_bytes, "id");
+  avm1_bytes.push(0x1c); // GetVariable
+  push_i32(&mut avm1_bytes, 1);
+  push_string(&mut avm1_bytes, "clearInterval");
+  avm1_bytes.push(0x3d); // CallFunction
+  avm1_bytes.push(0x17); // Pop
+  avm1_bytes.push(0x00);
+  let script = vm.create_script(avm1_bytes, None, None);
+  vm.run_to_completion(script);
+  vm.advance_time(1000f64);
+
+  let expected: Vec<String> = vec!["tick", "same time", "method", "tick"].into_iter().map(String::from).collect();
+  assert_eq!(*host.logs.borrow(), expected);
+}
+
+// Not a sample: timers only run when the host advances the time
+#[test]
+fn uncaught_timer_exceptions_are_reported() {
+  let host = LoggedHost::new();
+  let gc = GcScope::new();
+  let mut vm = Vm::new(&gc, &host, 11);
+
+  // setTimeout(function () { throw "timer"; }, 10); setTimeout(trace, 10, "next");
+  let mut avm1_bytes: Vec<u8> = Vec::new();
+  push_i32(&mut avm1_bytes, 10);
+  let mut body: Vec<u8> = Vec::new();
+  push_string(&mut body, "timer");
+  body.push(0x2a); // Throw
+  define_function(&mut avm1_bytes, &[], &body);
+  push_i32(&mut avm1_bytes, 2);
+  push_string(&mut avm1_bytes, "setTimeout");
+  avm1_bytes.push(0x3d); // CallFunction
+  avm1_bytes.push(0x17); // Pop
+  push_string(&mut avm1_bytes, "next");
+  push_i32(&mut avm1_bytes, 10);
+  push_string(&mut avm1_bytes, "trace");
+  avm1_bytes.push(0x1c); // GetVariable
+  push_i32(&mut avm1_bytes, 3);
+  push_string(&mut avm1_bytes, "setTimeout");
+  avm1_bytes.push(0x3d); // CallFunction
+  avm1_bytes.push(0x17); // Pop
+  avm1_bytes.push(0x00);
+  let script = vm.create_script(avm1_bytes, None, None);
+  vm.run_to_completion(script);
+  vm.advance_time(10f64);
+
+  let expected: Vec<String> = vec!["Warning: Uncaught exception: timer", "next"].into_iter().map(String::from).collect();
+  assert_eq!(*host.logs.borrow(), expected);
+}
+
+// Not a sample: timers only run when the host advances the time
+#[test]
+fn missed_interval_periods_are_merged() {
+  let host = LoggedHost::new();
+  let gc = GcScope::new();
+  let mut vm = Vm::new(&gc, &host, 11);
+
+  // setInterval(trace, 1, "tick");
+  let mut avm1_bytes: Vec<u8> = Vec::new();
+  push_string(&mut avm1_bytes, "tick");
+  push_i32(&mut avm1_bytes, 1);
+  push_string(&mut avm1_bytes, "trace");
+  avm1_bytes.push(0x1c); // GetVariable
+  push_i32(&mut avm1_bytes, 3);
+  push_string(&mut avm1_bytes, "setInterval");
+  avm1_bytes.push(0x3d); // CallFunction
+  avm1_bytes.push(0x17); // Pop
+  avm1_bytes.push(0x00);
+  let script = vm.create_script(avm1_bytes, None, None);
+  vm.run_to_completion(script);
+
+  vm.advance_time(1e9f64);
+  assert_eq!(host.logs.borrow().len(), 1);
+  vm.advance_time(0.5f64);
+  assert_eq!(host.logs.borrow().len(), 1);
+  vm.advance_time(0.5f64);
+
+  let expected: Vec<String> = vec!["tick", "tick"].into_iter().map(String::from).collect();
+  assert_eq!(*host.logs.borrow(), expected);
+}
+
+// Not a sample: the loaded document is registered on the host
+#[test]
+fn xml_parse_serialize_and_load() {
+  let mut avm1_bytes: Vec<u8> = Vec::new();
+  // x = new XML(source);
+  push_string(&mut avm1_bytes, "x");
+  push_string(&mut avm1_bytes, "<?xml version=\"1.0\"?><a k=\"v&amp;w\" b=\"2\" a=\"1\"><b>hi &lt;</b><!-- c --><c/></a>");
+  push_i32(&mut avm1_bytes, 1);
+  push_string(&mut avm1_bytes, "XML");
+  avm1_bytes.push(0x40); // NewObject
+  avm1_bytes.push(0x1d); // SetVariable
+  // trace(x.status); trace(x.firstChild.nodeName); trace(x.firstChild.attributes.k);
+  get_path(&mut avm1_bytes, "x", &["status"]);
+  avm1_bytes.push(0x26); // Trace
+  get_path(&mut avm1_bytes, "x", &["firstChild", "nodeName"]);
+  avm1_bytes.push(0x26); // Trace
+  get_path(&mut avm1_bytes, "x", &["firstChild", "attributes", "k"]);
+  avm1_bytes.push(0x26); // Trace
+  // trace(x.firstChild.firstChild.firstChild.nodeValue); trace(x.firstChild.childNodes.length);
+  get_path(&mut avm1_bytes, "x", &["firstChild", "firstChild", "firstChild", "nodeValue"]);
+  avm1_bytes.push(0x26); // Trace
+  get_path(&mut avm1_bytes, "x", &["firstChild", "childNodes", "length"]);
+  avm1_bytes.push(0x26); // Trace
+  // x.firstChild.appendChild(x.firstChild.firstChild.cloneNode(true)); trace(x);
+  push_i32(&mut avm1_bytes, 1);
+  push_i32(&mut avm1_bytes, 1);
+  get_path(&mut avm1_bytes, "x", &["firstChild", "firstChild"]);
+  push_string(&mut avm1_bytes, "cloneNode");
+  avm1_bytes.push(0x52); // CallMethod
+  push_i32(&mut avm1_bytes, 1);
+  get_path(&mut avm1_bytes, "x", &["firstChild"]);
+  push_string(&mut avm1_bytes, "appendChild");
+  avm1_bytes.push(0x52); // CallMethod
+  avm1_bytes.push(0x17); // Pop
+  get_path(&mut avm1_bytes, "x", &[]);
+  avm1_bytes.push(0x26); // Trace
+  // x.parseXML("<a><b></a>"); trace(x.status); x.parseXML("</a>"); trace(x.status);
+  for source in &["<a><b></a>", "</a>", "<a k=\"v></a>"] {
+    push_string(&mut avm1_bytes, source);
+    call_method(&mut avm1_bytes, "x", "parseXML", 1);
+    avm1_bytes.push(0x17); // Pop
+    get_path(&mut avm1_bytes, "x", &["status"]);
+    avm1_bytes.push(0x26); // Trace
+  }
+  // x.onLoad = function (ok) { trace(ok); trace(this); }; x.load("data.xml"); x.load("missing.xml");
+  get_path(&mut avm1_bytes, "x", &[]);
+  push_string(&mut avm1_bytes, "onLoad");
+  let mut body: Vec<u8> = Vec::new();
+  push_string(&mut body, "ok");
+  body.push(0x1c); // GetVariable
+  body.push(0x26); // Trace
+  push_string(&mut body, "this");
+  body.push(0x1c); // GetVariable
+  body.push(0x26); // Trace
+  define_function(&mut avm1_bytes, &["ok"], &body);
+  avm1_bytes.push(0x4f); // SetMember
+  for url in &["data.xml", "missing.xml"] {
+    push_string(&mut avm1_bytes, url);
+    call_method(&mut avm1_bytes, "x", "load", 1);
+    avm1_bytes.push(0x17); // Pop
+  }
+  avm1_bytes.push(0x00);
+
+  let host = LoggedHost::new();
+  host.set_url_content("data.xml", "<r>\n  <s t='1' />\n</r>");
+  let expected: Vec<String> = vec![
+    "0",
+    "a",
+    "v&w",
+    "hi <",
+    "2",
+    "<?xml version=\"1.0\"?><a k=\"v&amp;w\" b=\"2\" a=\"1\"><b>hi &lt;</b><c /><b>hi &lt;</b></a>",
+    "-9",
+    "-10",
+    "-8",
+    "load(data.xml)",
+    "true",
+    "<r>\n  <s t=\"1\" />\n</r>",
+    "load(missing.xml)",
+    "false",
+    "<r>\n  <s t=\"1\" />\n</r>",
+  ]
+    .into_iter()
+    .map(String::from)
+    .collect();
+  assert_eq!(run_avm1_bytes_with_host(&host, avm1_bytes), expected);
+}
+
+/// Runs raw AVM1 bytecode with `host` and returns its logs.
+fn run_avm1_bytes_with_host(host: &LoggedHost, avm1_bytes: Vec<u8>) -> Vec<String> {
+  let gc = GcScope::new();
+  let mut vm = Vm::new(&gc, host, 11);
+  let script_id = vm.create_script(avm1_bytes, None, None);
+  vm.run_to_completion(script_id);
+  let logs = host.logs.borrow().clone();
+  logs
+}
+
+/// Appends a `Push` action for a single string.
+fn push_string(avm1_bytes: &mut Vec<u8>, value: &str) {
+  let len = (value.len() + 2) as u16;
+  avm1_bytes.extend_from_slice(&[0x96, len as u8, (len >> 8) as u8, 0x00]);
+  avm1_bytes.extend_from_slice(value.as_bytes());
+  avm1_bytes.push(0x00);
+}
+
+/// Appends a `ConstantPool` action.
+fn constant_pool(avm1_bytes: &mut Vec<u8>, values: &[&str]) {
+  let len = 2 + values.iter().map(|v| v.len() + 1).sum::<usize>();
+  avm1_bytes.extend_from_slice(&[0x88, len as u8, (len >> 8) as u8, values.len() as u8, 0x00]);
+  for value in values {
+    avm1_bytes.extend_from_slice(value.as_bytes());
+    avm1_bytes.push(0x00);
+  }
+}
+
+/// Appends the actions calling `variable.method(...)`, with the arguments already pushed.
+fn call_method(avm1_bytes: &mut Vec<u8>, variable: &str, method: &str, arg_count: i32) {
+  push_i32(avm1_bytes, arg_count);
+  push_string(avm1_bytes, variable);
+  avm1_bytes.push(0x1c); // GetVariable
+  push_string(avm1_bytes, method);
+  avm1_bytes.push(0x52); // CallMethod
+}
+
+/// Appends an anonymous `DefineFunction` action followed by its body.
+fn define_function(avm1_bytes: &mut Vec<u8>, parameters: &[&str], body: &[u8]) {
+  let len = 1 + 2 + parameters.iter().map(|p| p.len() + 1).sum::<usize>() + 2;
+  avm1_bytes.extend_from_slice(&[0x9b, len as u8, (len >> 8) as u8, 0x00]);
+  avm1_bytes.extend_from_slice(&(parameters.len() as u16).to_le_bytes());
+  for parameter in parameters {
+    avm1_bytes.extend_from_slice(parameter.as_bytes());
+    avm1_bytes.push(0x00);
+  }
+  avm1_bytes.extend_from_slice(&(body.len() as u16).to_le_bytes());
+  avm1_bytes.extend_from_slice(body);
+}
+
+/// Appends the actions reading `variable.member1.member2...`.
+fn get_path(avm1_bytes: &mut Vec<u8>, variable: &str, members: &[&str]) {
+  push_string(avm1_bytes, variable);
+  avm1_bytes.push(0x1c); // GetVariable
+  for member in members {
+    push_string(avm1_bytes, member);
+    avm1_bytes.push(0x4e); // GetMember
+  }
+}
+
+/// Appends a `Push` action for a single signed 32-bit integer.
+fn push_i32(avm1_bytes: &mut Vec<u8>, value: i32) {
+  avm1_bytes.extend_from_slice(&[0x96, 0x05, 0x00, 0x07]);
+  avm1_bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+// Not a sample: the frame script must be registered on the host
+#[test]
+fn call_frame_script() {
+  let host = LoggedHost::new();
+
+  let mut frame_bytes: Vec<u8> = Vec::new();
+  constant_pool(&mut frame_bytes, &["callee"]);
+  push_string(&mut frame_bytes, "a");
+  push_string(&mut frame_bytes, "set by frame");
+  frame_bytes.push(0x1d); // SetVariable
+  frame_bytes.push(0x00);
+  host.set_frame_script("f", FrameScript { target: None, code: frame_bytes });
+
+  let mut avm1_bytes: Vec<u8> = Vec::new();
+  constant_pool(&mut avm1_bytes, &["caller"]);
+  push_string(&mut avm1_bytes, "f");
+  avm1_bytes.extend_from_slice(&[0x9e, 0x00, 0x00]); // Call
+  push_string(&mut avm1_bytes, "a");
+  avm1_bytes.push(0x1c); // GetVariable
+  avm1_bytes.push(0x26); // Trace
+  avm1_bytes.extend_from_slice(&[0x96, 0x02, 0x00, 0x08, 0x00]); // Push constant 0
+  avm1_bytes.push(0x26); // Trace
+  avm1_bytes.push(0x00);
+
+  let expected: Vec<String> = vec![String::from("set by frame"), String::from("caller")];
+  assert_eq!(run_avm1_bytes_with_host(&host, avm1_bytes), expected);
+}
+
+#[test]
+fn default_host_clock() {
+  struct MinimalHost;
+  impl Host for MinimalHost {
+    fn trace(&self, _message: &str) -> () {}
+    fn warn(&self, _warning: &Warning) -> () {}
+    fn random(&self) -> f64 {
+      0f64
+    }
+  }
+
+  let host = MinimalHost;
+  assert_eq!(host.get_time(), 0f64);
+  assert_eq!(host.get_date(), 0f64);
+  assert_eq!(host.get_timezone_offset(), 0f64);
+}
+
+#[test]
+fn hosts_do_not_share_random_state() {
+  let a = LoggedHost::new();
+  let b = LoggedHost::new();
+  let first: Vec<f64> = (0..4).map(|_| a.random()).collect();
+  let second: Vec<f64> = (0..4).map(|_| b.random()).collect();
+  assert_eq!(first, second);
+  let rng = XorShift64::new(LOGGED_HOST_SEED);
+  let expected: Vec<f64> = (0..4).map(|_| rng.next_f64()).collect();
+  assert_eq!(first, expected);
+}
+
+#[test]
+fn logged_host_is_replayable() {
+  let host = LoggedHost::new();
+  let first: Vec<f64> = (0..4).map(|_| host.random()).collect();
+  host.set_random_seed(LOGGED_HOST_SEED);
+  let second: Vec<f64> = (0..4).map(|_| host.random()).collect();
+
+  assert_eq!(first, second);
+  assert!(first.iter().all(|&x| 0f64 <= x && x < 1f64));
+  host.set_time(1500f64);
+  assert_eq!(host.get_time(), 1500f64);
+}
+
 //#[test]
 //fn one_plus_one_equals_two() {
 //  let host = LoggedHost::new();
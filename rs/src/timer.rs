@@ -0,0 +1,106 @@
+use crate::values::AvmValue;
+use crate::values::object::AvmObjectRef;
+
+/// Minimum delay between two executions of a timer, in milliseconds.
+///
+/// It prevents zero-delay intervals from firing forever when the time advances.
+const MIN_INTERVAL: f64 = 1f64;
+
+/// Function called when a timer fires.
+#[derive(Debug, Clone)]
+pub enum TimerCallback<'gc> {
+  /// `setInterval(func, delay, ...args)`
+  Function(AvmObjectRef<'gc>),
+  /// `setInterval(obj, "method", delay, ...args)`: the method is resolved each time the timer fires.
+  Method(AvmObjectRef<'gc>, String),
+}
+
+/// Timer created by `setInterval` or `setTimeout`.
+#[derive(Debug, Clone)]
+pub struct Timer<'gc> {
+  pub callback: TimerCallback<'gc>,
+  /// Delay before the first execution and between executions, in milliseconds
+  pub interval: f64,
+  pub args: Vec<AvmValue<'gc>>,
+  /// `true` for `setInterval`, `false` for `setTimeout`
+  pub repeat: bool,
+}
+
+#[derive(Debug)]
+struct ScheduledTimer<'gc> {
+  id: i32,
+  /// Virtual time of the next execution
+  next_fire: f64,
+  timer: Timer<'gc>,
+}
+
+/// Timers of a VM, driven by a virtual clock advanced by the host.
+#[derive(Debug)]
+pub(crate) struct TimerQueue<'gc> {
+  next_id: i32,
+  /// Current virtual time, in milliseconds
+  time: f64,
+  /// Active timers, in registration order
+  timers: Vec<ScheduledTimer<'gc>>,
+}
+
+impl<'gc> TimerQueue<'gc> {
+  pub(crate) fn new() -> Self {
+    TimerQueue { next_id: 1, time: 0f64, timers: Vec::new() }
+  }
+
+  /// Registers a timer, returns its id.
+  pub(crate) fn add(&mut self, timer: Timer<'gc>) -> i32 {
+    let id = self.next_id;
+    self.next_id += 1;
+    let interval = if timer.interval.is_nan() { 0f64 } else { timer.interval };
+    let timer = Timer { interval: interval.max(MIN_INTERVAL), ..timer };
+    let next_fire = self.time + timer.interval;
+    self.timers.push(ScheduledTimer { id, next_fire, timer });
+    id
+  }
+
+  /// Removes a timer, returns `true` if it was active.
+  pub(crate) fn remove(&mut self, id: i32) -> bool {
+    let len = self.timers.len();
+    self.timers.retain(|t| t.id != id);
+    self.timers.len() != len
+  }
+
+  /// Returns the next timer due at or before `end`, and moves the clock to its execution time.
+  ///
+  /// Timers due at the same time are returned in registration order. Intervals are rescheduled
+  /// after `end`: the periods missed during a single advance are merged into one execution.
+  /// Timeouts are removed.
+  pub(crate) fn pop_due(&mut self, end: f64) -> Option<Timer<'gc>> {
+    let mut next: Option<usize> = None;
+    for (i, scheduled) in self.timers.iter().enumerate() {
+      let is_earlier = match next {
+        Some(n) => scheduled.next_fire < self.timers[n].next_fire,
+        None => true,
+      };
+      if scheduled.next_fire <= end && is_earlier {
+        next = Some(i);
+      }
+    }
+    let index = next?;
+    let scheduled = &mut self.timers[index];
+    self.time = scheduled.next_fire;
+    if scheduled.timer.repeat {
+      let interval = scheduled.timer.interval;
+      scheduled.next_fire += ((end - scheduled.next_fire) / interval).floor().max(0f64) * interval + interval;
+      Some(scheduled.timer.clone())
+    } else {
+      Some(self.timers.remove(index).timer)
+    }
+  }
+
+  /// Moves the clock to `time`, once all the timers due before it ran.
+  pub(crate) fn set_time(&mut self, time: f64) -> () {
+    self.time = time;
+  }
+
+  pub(crate) fn time(&self) -> f64 {
+    self.time
+  }
+}
@@ -6,16 +6,17 @@ use avm1_tree as avm1;
 pub use self::object::AvmObject;
 pub use self::string::AvmString;
 use crate::context::Context;
-use crate::values::object::AvmObjectRef;
+use crate::values::object::{AvmObjectClass, AvmObjectRef};
 
 pub mod object;
+pub mod property_map;
 mod string;
 
 pub trait AvmConvert<'gc> {
   fn to_avm_boolean(&self) -> AvmBoolean;
   fn to_avm_number(&self) -> AvmNumber;
-  fn to_avm_primitive<C: Context<'gc>>(&self, ctx: &mut C, hint: ToPrimitiveHint) -> Result<AvmPrimitive<'gc>, ()>;
-  fn to_avm_string<C: Context<'gc>>(&self, ctx: &mut C) -> Result<Gc<'gc, AvmString>, GcAllocErr>;
+  fn to_avm_primitive<C: Context<'gc> + ?Sized>(&self, ctx: &mut C, hint: ToPrimitiveHint) -> Result<AvmPrimitive<'gc>, AvmValue<'gc>>;
+  fn to_avm_string<C: Context<'gc> + ?Sized>(&self, ctx: &mut C) -> Result<Gc<'gc, AvmString>, AvmValue<'gc>>;
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Trace)]
@@ -30,12 +31,12 @@ impl<'gc> AvmConvert<'gc> for AvmUndefined {
     AvmNumber::NAN
   }
 
-  fn to_avm_primitive<C: Context<'gc>>(&self, _: &mut C, _: ToPrimitiveHint) -> Result<AvmPrimitive<'gc>, ()> {
+  fn to_avm_primitive<C: Context<'gc> + ?Sized>(&self, _: &mut C, _: ToPrimitiveHint) -> Result<AvmPrimitive<'gc>, AvmValue<'gc>> {
     Ok(AvmPrimitive::UNDEFINED)
   }
 
-  fn to_avm_string<C: Context<'gc>>(&self, ctx: &mut C) -> Result<Gc<'gc, AvmString>, GcAllocErr> {
-    ctx.string(String::from(if ctx.swf_version() >= 7 { "undefined" } else { "" }))
+  fn to_avm_string<C: Context<'gc> + ?Sized>(&self, ctx: &mut C) -> Result<Gc<'gc, AvmString>, AvmValue<'gc>> {
+    Ok(ctx.string(String::from(if ctx.swf_version() >= 7 { "undefined" } else { "" })).unwrap())
   }
 }
 
@@ -51,12 +52,12 @@ impl<'gc> AvmConvert<'gc> for AvmNull {
     AvmNumber::ZERO
   }
 
-  fn to_avm_primitive<C: Context<'gc>>(&self, _: &mut C, _: ToPrimitiveHint) -> Result<AvmPrimitive<'gc>, ()> {
+  fn to_avm_primitive<C: Context<'gc> + ?Sized>(&self, _: &mut C, _: ToPrimitiveHint) -> Result<AvmPrimitive<'gc>, AvmValue<'gc>> {
     Ok(AvmPrimitive::NULL)
   }
 
-  fn to_avm_string<C: Context<'gc>>(&self, ctx: &mut C) -> Result<Gc<'gc, AvmString>, GcAllocErr> {
-    ctx.string(String::from("null"))
+  fn to_avm_string<C: Context<'gc> + ?Sized>(&self, ctx: &mut C) -> Result<Gc<'gc, AvmString>, AvmValue<'gc>> {
+    Ok(ctx.string(String::from("null")).unwrap())
   }
 }
 
@@ -68,14 +69,68 @@ impl AvmNumber {
   const ONE: Self = AvmNumber(1f64);
   const NAN: Self = AvmNumber(::std::f64::NAN);
 
+  /// Creates a new number, `NaN` payloads are replaced by the canonical `NaN`.
+  ///
+  /// The sign of zero is preserved because it is observable (`1 / -0` is `-Infinity`).
   pub const fn new(value: f64) -> AvmNumber {
-    // TODO: Handle normalization of `NaN` and `-0` to canonical `NaN` and `+0`.
-    AvmNumber(value)
+    if value.is_nan() {
+      AvmNumber(f64::NAN)
+    } else {
+      AvmNumber(value)
+    }
   }
 
   pub fn value(&self) -> f64 {
     self.0
   }
+
+  /// Converts the number to a signed 32-bit integer (truncation modulo `2^32`).
+  ///
+  /// The conversion follows ES-262-3 section 9.5 ("ToInt32"): `NaN` and infinities are `0`.
+  pub fn to_int32(&self) -> i32 {
+    if !self.0.is_finite() {
+      return 0;
+    }
+    let int = self.0.trunc() % 4294967296f64;
+    (int as i64) as u32 as i32
+  }
+}
+
+/// Formats a number the way Flash Player does: up to 15 significant digits, using the
+/// exponential notation for exponents below `-4` or above `14`.
+pub(crate) fn number_to_string(value: f64) -> String {
+  if value.is_nan() {
+    return String::from("NaN");
+  } else if value == 0f64 {
+    return String::from("0");
+  } else if value.is_infinite() {
+    return String::from(if value > 0f64 { "Infinity" } else { "-Infinity" });
+  }
+
+  // `d.dddddddddddddde<exp>`, rounded to 15 significant digits
+  let scientific = format!("{:.14e}", value.abs());
+  let (mantissa, exponent) = scientific.split_at(scientific.find('e').unwrap());
+  let exponent: i32 = exponent[1..].parse().unwrap();
+  let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+  let digits: &str = digits.trim_end_matches('0');
+  let sign = if value < 0f64 { "-" } else { "" };
+
+  if !(-4..15).contains(&exponent) {
+    let (first, rest) = digits.split_at(1);
+    let rest = if rest.is_empty() { String::new() } else { format!(".{}", rest) };
+    let exponent_sign = if exponent < 0 { "-" } else { "+" };
+    format!("{}{}{}e{}{}", sign, first, rest, exponent_sign, exponent.abs())
+  } else if exponent < 0 {
+    let zeros = "0".repeat((-exponent - 1) as usize);
+    format!("{}0.{}{}", sign, zeros, digits)
+  } else {
+    let int_len = (exponent + 1) as usize;
+    if digits.len() <= int_len {
+      format!("{}{}{}", sign, digits, "0".repeat(int_len - digits.len()))
+    } else {
+      format!("{}{}.{}", sign, &digits[..int_len], &digits[int_len..])
+    }
+  }
 }
 
 impl<'gc> AvmConvert<'gc> for AvmNumber {
@@ -87,12 +142,12 @@ impl<'gc> AvmConvert<'gc> for AvmNumber {
     self.clone()
   }
 
-  fn to_avm_primitive<C: Context<'gc>>(&self, _: &mut C, _: ToPrimitiveHint) -> Result<AvmPrimitive<'gc>, ()> {
+  fn to_avm_primitive<C: Context<'gc> + ?Sized>(&self, _: &mut C, _: ToPrimitiveHint) -> Result<AvmPrimitive<'gc>, AvmValue<'gc>> {
     Ok(AvmPrimitive::Number(self.clone()))
   }
 
-  fn to_avm_string<C: Context<'gc>>(&self, ctx: &mut C) -> Result<Gc<'gc, AvmString>, GcAllocErr> {
-    ctx.string(format!("{}", self.0))
+  fn to_avm_string<C: Context<'gc> + ?Sized>(&self, ctx: &mut C) -> Result<Gc<'gc, AvmString>, AvmValue<'gc>> {
+    Ok(ctx.string(number_to_string(self.0)).unwrap())
   }
 }
 
@@ -125,12 +180,12 @@ impl<'gc> AvmConvert<'gc> for AvmBoolean {
     }
   }
 
-  fn to_avm_primitive<C: Context<'gc>>(&self, _: &mut C, _: ToPrimitiveHint) -> Result<AvmPrimitive<'gc>, ()> {
+  fn to_avm_primitive<C: Context<'gc> + ?Sized>(&self, _: &mut C, _: ToPrimitiveHint) -> Result<AvmPrimitive<'gc>, AvmValue<'gc>> {
     Ok(AvmPrimitive::Boolean(self.clone()))
   }
 
-  fn to_avm_string<C: Context<'gc>>(&self, ctx: &mut C) -> Result<Gc<'gc, AvmString>, GcAllocErr> {
-    ctx.string(String::from(if self.0 { "true" } else { "false" }))
+  fn to_avm_string<C: Context<'gc> + ?Sized>(&self, ctx: &mut C) -> Result<Gc<'gc, AvmString>, AvmValue<'gc>> {
+    Ok(ctx.string(String::from(if self.0 { "true" } else { "false" })).unwrap())
   }
 }
 
@@ -144,19 +199,10 @@ pub enum AvmValue<'gc> {
   Undefined(AvmUndefined),
 }
 
-// Corresponds to a data equality (NaN is equal to NaN)
+// Corresponds to a data equality: see `AvmValue::same_value`
 impl<'gc> PartialEq for AvmValue<'gc> {
-  fn eq(&self, other: &AvmValue) -> bool {
-    match (self, other) {
-      (&AvmValue::Boolean(ref left), &AvmValue::Boolean(ref right)) => left == right,
-      (&AvmValue::Undefined(_), &AvmValue::Undefined(_)) => true,
-      (&AvmValue::String(ref left), &AvmValue::String(ref right)) => left.value() == right.value(),
-      (_, _) => false,
-    }
-  }
-
-  fn ne(&self, other: &AvmValue<'gc>) -> bool {
-    !self.eq(other)
+  fn eq(&self, other: &AvmValue<'gc>) -> bool {
+    self.same_value(other)
   }
 }
 
@@ -169,6 +215,28 @@ impl<'gc> AvmValue<'gc> {
   pub const FALSE: Self = AvmValue::Boolean(AvmBoolean::FALSE);
   pub const TRUE: Self = AvmValue::Boolean(AvmBoolean::TRUE);
 
+  /// Compares two values using the SameValue algorithm.
+  ///
+  /// `NaN` is equal to itself, `+0` and `-0` are different and objects are compared by identity.
+  pub fn same_value(&self, other: &AvmValue<'gc>) -> bool {
+    match (self, other) {
+      (&AvmValue::Boolean(ref left), &AvmValue::Boolean(ref right)) => left == right,
+      (&AvmValue::Null(_), &AvmValue::Null(_)) => true,
+      (&AvmValue::Number(ref left), &AvmValue::Number(ref right)) => {
+        let (left, right) = (left.value(), right.value());
+        if left.is_nan() || right.is_nan() {
+          left.is_nan() && right.is_nan()
+        } else {
+          left == right && left.is_sign_negative() == right.is_sign_negative()
+        }
+      }
+      (&AvmValue::Object(ref left), &AvmValue::Object(ref right)) => left.ptr_eq(right),
+      (&AvmValue::String(ref left), &AvmValue::String(ref right)) => left.value() == right.value(),
+      (&AvmValue::Undefined(_), &AvmValue::Undefined(_)) => true,
+      (_, _) => false,
+    }
+  }
+
   pub fn legacy_boolean(value: bool, swf_version: u8) -> AvmValue<'gc> {
     if swf_version < 5 {
       if value {
@@ -210,11 +278,11 @@ impl<'gc> AvmValue<'gc> {
       &AvmValue::Boolean(ref v) => v.to_avm_boolean(),
       &AvmValue::Number(ref v) => v.to_avm_boolean(),
       &AvmValue::String(ref v) => v.to_avm_boolean(),
-      &AvmValue::Object(_) => unimplemented!("ToBoolean(Object)"),
+      &AvmValue::Object(_) => AvmBoolean::TRUE,
     }
   }
 
-  pub fn to_avm_string<C: Context<'gc>>(&self, ctx: &mut C) -> Result<Gc<'gc, AvmString>, GcAllocErr> {
+  pub fn to_avm_string<C: Context<'gc> + ?Sized>(&self, ctx: &mut C) -> Result<Gc<'gc, AvmString>, AvmValue<'gc>> {
     match self {
       &AvmValue::Undefined(ref v) => v.to_avm_string(ctx),
       &AvmValue::Null(ref v) => v.to_avm_string(ctx),
@@ -253,14 +321,60 @@ impl<'gc> AvmValue<'gc> {
     }
   }
 
-  pub fn to_avm_primitive<C: Context<'gc>>(&self, ctx: &mut C, hint: ToPrimitiveHint) -> Result<AvmPrimitive<'gc>, ()> {
+  pub fn to_avm_primitive<C: Context<'gc> + ?Sized>(&self, ctx: &mut C, hint: ToPrimitiveHint) -> Result<AvmPrimitive<'gc>, AvmValue<'gc>> {
     match self {
       &AvmValue::Undefined(ref v) => v.to_avm_primitive(ctx, hint),
       &AvmValue::Null(ref v) => v.to_avm_primitive(ctx, hint),
       &AvmValue::Boolean(ref v) => v.to_avm_primitive(ctx, hint),
       &AvmValue::Number(ref v) => v.to_avm_primitive(ctx, hint),
-      &AvmValue::String(ref v) => v.to_avm_primitive(ctx, hint),
-      &AvmValue::Object(_) => unimplemented!("ToPrimitive(Object)"),
+      &AvmValue::String(ref v) => Ok(AvmPrimitive::String(Gc::clone(v))),
+      &AvmValue::Object(ref v) => v.to_avm_primitive(ctx, hint),
+    }
+  }
+
+  /// Converts the current value to an `AvmNumber`, calling `valueOf` on objects.
+  ///
+  /// The conversion follows ES-262-3 section 9.3 ("ToNumber"). Returns the exception thrown by
+  /// `valueOf` or `toString`.
+  pub fn to_avm_number_with_context<C: Context<'gc> + ?Sized>(&self, ctx: &mut C) -> Result<AvmNumber, AvmValue<'gc>> {
+    match self {
+      &AvmValue::Object(ref v) => v.to_avm_primitive(ctx, ToPrimitiveHint::Number).map(|primitive| primitive.to_avm_number()),
+      v => Ok(v.to_avm_number()),
+    }
+  }
+
+  /// Converts the current value to an `AvmNumber` for the arithmetic actions, depending on the SWF version.
+  ///
+  /// SWF 4 uses the legacy rules. Later versions follow ES-262-3 section 9.3 ("ToNumber") and call
+  /// `valueOf` on objects, but `undefined` converts to `0` before SWF 7.
+  pub fn to_avm_number_versioned<C: Context<'gc> + ?Sized>(&self, ctx: &mut C) -> Result<AvmNumber, AvmValue<'gc>> {
+    let swf_version = ctx.swf_version();
+    match self {
+      v if swf_version < 5 => Ok(v.legacy_to_avm_number()),
+      &AvmValue::Undefined(_) if swf_version < 7 => Ok(AvmNumber::ZERO),
+      v => v.to_avm_number_with_context(ctx),
+    }
+  }
+
+  /// Returns the result of the `typeof` operator.
+  pub fn type_of(&self) -> &'static str {
+    match self {
+      &AvmValue::Undefined(_) => "undefined",
+      &AvmValue::Null(_) => "null",
+      &AvmValue::Boolean(_) => "boolean",
+      &AvmValue::Number(_) => "number",
+      &AvmValue::String(_) => "string",
+      &AvmValue::Object(ref v) => {
+        let obj = v.0.borrow();
+        if obj.callable.is_some() {
+          "function"
+        } else if obj.class == AvmObjectClass::MovieClip {
+          // Display objects provided by the host
+          "movieclip"
+        } else {
+          "object"
+        }
+      }
     }
   }
 }
@@ -291,7 +405,7 @@ impl<'gc> AvmPrimitive<'gc> {
   pub const FALSE: Self = AvmPrimitive::Boolean(AvmBoolean::FALSE);
   pub const TRUE: Self = AvmPrimitive::Boolean(AvmBoolean::TRUE);
 
-  pub fn to_avm_string<C: Context<'gc>>(&self, ctx: &mut C) -> Result<Gc<'gc, AvmString>, GcAllocErr> {
+  pub fn to_avm_string<C: Context<'gc> + ?Sized>(&self, ctx: &mut C) -> Result<Gc<'gc, AvmString>, AvmValue<'gc>> {
     match self {
       &AvmPrimitive::Undefined(ref v) => v.to_avm_string(ctx),
       &AvmPrimitive::Null(ref v) => v.to_avm_string(ctx),
@@ -329,3 +443,15 @@ impl<'gc> TryFrom<AvmValue<'gc>> for AvmPrimitive<'gc> {
     }
   }
 }
+
+impl<'gc> From<AvmPrimitive<'gc>> for AvmValue<'gc> {
+  fn from(value: AvmPrimitive<'gc>) -> Self {
+    match value {
+      AvmPrimitive::Undefined(v) => AvmValue::Undefined(v),
+      AvmPrimitive::Null(v) => AvmValue::Null(v),
+      AvmPrimitive::Boolean(v) => AvmValue::Boolean(v),
+      AvmPrimitive::Number(v) => AvmValue::Number(v),
+      AvmPrimitive::String(v) => AvmValue::String(v),
+    }
+  }
+}
@@ -1,21 +1,106 @@
 use ::std::collections::hash_map::HashMap;
 use std::convert::TryFrom;
+use std::ops::Deref;
 
 use scoped_gc::{Gc, GcAllocErr, GcRefCell, GcScope};
 
-use crate::avm1::Scope;
+use crate::avm1::{Scope, TargetId};
 use crate::context::{AvmResult, CallContext, Context};
 use crate::values::{AvmBoolean, AvmConvert, AvmNull, AvmNumber, AvmPrimitive, AvmString, ToPrimitiveHint};
+use crate::values::property_map::PropertyMap;
 
 use self::super::AvmValue;
 
+/// Property flags used by `ASSetPropFlags`
+pub const DONT_ENUM: u16 = 1 << 0;
+pub const DONT_DELETE: u16 = 1 << 1;
+pub const READ_ONLY: u16 = 1 << 2;
+/// Hides the property from movies below SWF6
+pub const VERSION_6: u16 = 1 << 7;
+/// Hides the property from movies below SWF7
+pub const VERSION_7: u16 = 1 << 10;
+/// Hides the property from movies below SWF8
+pub const VERSION_8: u16 = 1 << 12;
+const VERSION_MASK: u16 = VERSION_6 | VERSION_7 | VERSION_8;
+
 #[derive(Debug, Clone, Trace)]
 pub struct AvmObjectProperty<'gc> {
   pub read_only: bool,
   pub enumerable: bool,
   pub deletable: bool,
   pub internal: bool,
+  /// `VERSION_*` flags restricting the visibility of the property
+  pub version: u16,
   pub value: AvmValue<'gc>,
+  /// Getter and setter of virtual properties (`value` is unused)
+  pub accessor: Option<AvmAccessor<'gc>>,
+}
+
+/// Functions of a property created with `Object.prototype.addProperty`
+#[derive(Debug, Clone, Trace)]
+pub struct AvmAccessor<'gc> {
+  pub getter: AvmObjectRef<'gc>,
+  /// `None` for read-only properties
+  pub setter: Option<AvmObjectRef<'gc>>,
+}
+
+impl<'gc> AvmObjectProperty<'gc> {
+  /// Creates a data property with the default attributes.
+  pub fn data(value: AvmValue<'gc>) -> Self {
+    AvmObjectProperty {
+      read_only: false,
+      enumerable: true,
+      deletable: true,
+      internal: true,
+      version: 0,
+      value,
+      accessor: None,
+    }
+  }
+
+  /// Creates an accessor property with the default attributes.
+  pub fn accessor(getter: AvmObjectRef<'gc>, setter: Option<AvmObjectRef<'gc>>) -> Self {
+    AvmObjectProperty {
+      accessor: Some(AvmAccessor { getter, setter }),
+      ..AvmObjectProperty::data(AvmValue::UNDEFINED)
+    }
+  }
+
+  /// Returns the flags of this property, in the format used by `ASSetPropFlags`.
+  pub fn flags(&self) -> u16 {
+    let mut flags = self.version;
+    if !self.enumerable {
+      flags |= DONT_ENUM;
+    }
+    if !self.deletable {
+      flags |= DONT_DELETE;
+    }
+    if self.read_only {
+      flags |= READ_ONLY;
+    }
+    flags
+  }
+
+  pub fn set_flags(&mut self, flags: u16) -> () {
+    self.enumerable = flags & DONT_ENUM == 0;
+    self.deletable = flags & DONT_DELETE == 0;
+    self.read_only = flags & READ_ONLY != 0;
+    self.version = flags & VERSION_MASK;
+  }
+
+  /// Tests if the property is visible to a movie with the provided SWF version.
+  pub fn is_visible(&self, swf_version: u8) -> bool {
+    let min_version: u8 = if self.version & VERSION_8 != 0 {
+      8
+    } else if self.version & VERSION_7 != 0 {
+      7
+    } else if self.version & VERSION_6 != 0 {
+      6
+    } else {
+      0
+    };
+    swf_version >= min_version
+  }
 }
 
 #[derive(Debug, Clone, Trace)]
@@ -39,31 +124,84 @@ impl<'gc> TryFrom<AvmValue<'gc>> for AvmObjectPrototype<'gc> {
   }
 }
 
+/// Internal `[[Class]]` of an object, it selects the builtin behavior of the object.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Trace)]
+pub enum AvmObjectClass {
+  Object,
+  Function,
+  Array,
+  Boolean,
+  Number,
+  String,
+  Date,
+  MovieClip,
+  XML,
+  XMLNode,
+  /// `super` object of a function call, see `Vm::new_super`
+  Super,
+}
+
+impl AvmObjectClass {
+  /// Returns the class name, as reported by `Object.prototype.toString`.
+  pub fn name(&self) -> &'static str {
+    match self {
+      AvmObjectClass::Object => "Object",
+      AvmObjectClass::Function => "Function",
+      AvmObjectClass::Array => "Array",
+      AvmObjectClass::Boolean => "Boolean",
+      AvmObjectClass::Number => "Number",
+      AvmObjectClass::String => "String",
+      AvmObjectClass::Date => "Date",
+      AvmObjectClass::MovieClip => "MovieClip",
+      AvmObjectClass::XML => "XML",
+      AvmObjectClass::XMLNode => "XMLNode",
+      AvmObjectClass::Super => "Object",
+    }
+  }
+}
+
 #[derive(Debug, Trace)]
 pub struct AvmObject<'gc> {
   // Internal `[[class]]`
-  pub class: &'static str,
+  pub class: AvmObjectClass,
 
   // internal `[[prototype]]` (`__proto__`), not `prototype` property
   pub prototype: AvmObjectPrototype<'gc>,
 
-  // TODO: Insertion order
-  pub properties: HashMap<String, AvmObjectProperty<'gc>>,
+  /// Own properties, in insertion order
+  pub properties: PropertyMap<'gc>,
 
   pub callable: Option<AvmCallable<'gc>>,
+
+  /// Callbacks registered with `Object.prototype.watch`, by property name
+  pub watchers: HashMap<String, AvmWatcher<'gc>>,
+
+  /// Internal `[[Value]]` of the objects created by builtin constructors, such as `new String(s)`,
+  /// or the `this` value of a `super` object
+  pub value: Option<AvmValue<'gc>>,
+}
+
+#[derive(Debug, Clone, Trace)]
+pub struct AvmWatcher<'gc> {
+  pub callback: AvmObjectRef<'gc>,
+  pub user_data: AvmValue<'gc>,
+  /// Set while the callback runs: assignments made by the callback itself are not intercepted
+  pub running: bool,
 }
 
 impl<'gc> AvmObject<'gc> {
   pub fn new(gc: &'gc GcScope<'gc>, prototype: Option<AvmObjectRef<'gc>>) -> Result<AvmObjectRef<'gc>, GcAllocErr> {
     gc
       .alloc(GcRefCell::new(AvmObject {
-        class: "Object",
+        class: AvmObjectClass::Object,
         prototype: match prototype {
           Some(p) => AvmObjectPrototype::Object(p),
           None => AvmObjectPrototype::Null(AvmNull),
         },
-        properties: HashMap::new(),
+        properties: PropertyMap::new(),
         callable: None,
+        watchers: HashMap::new(),
+        value: None,
       }))
       .map(AvmObjectRef)
   }
@@ -71,39 +209,198 @@ impl<'gc> AvmObject<'gc> {
   pub fn new_callable(gc: &'gc GcScope<'gc>, callable: AvmCallable<'gc>) -> Result<AvmObjectRef<'gc>, GcAllocErr> {
     gc
       .alloc(GcRefCell::new(AvmObject {
-        class: "Function",
+        class: AvmObjectClass::Function,
         prototype: AvmObjectPrototype::Null(AvmNull),
-        properties: HashMap::new(),
+        properties: PropertyMap::new(),
         callable: Some(callable),
+        watchers: HashMap::new(),
+        value: None,
       }))
       .map(AvmObjectRef)
   }
 
   pub fn set(&mut self, key: String, value: AvmValue<'gc>) {
-    let property = AvmObjectProperty {
-      read_only: false,
-      enumerable: true,
-      deletable: true,
-      internal: true,
-      value,
-    };
+    if key == "__proto__" {
+      // Assigning a primitive value cuts the prototype chain
+      self.prototype = AvmObjectPrototype::try_from(value).unwrap_or(AvmObjectPrototype::Null(AvmNull));
+      return;
+    }
+    if self.class == AvmObjectClass::Array {
+      if key == "length" {
+        self.set_array_length(value);
+        return;
+      }
+      if let Some(index) = array_index(&key) {
+        if index >= self.array_length() {
+          self.set_array_length(AvmValue::number(f64::from(index) + 1f64));
+        }
+      }
+    }
+    if let Some(property) = self.properties.get_mut(&key) {
+      // Existing properties keep their attributes
+      if !property.read_only {
+        property.value = value;
+        property.accessor = None;
+      }
+      return;
+    }
+    self.properties.insert(key, AvmObjectProperty::data(value));
+  }
+
+  /// Defines (or replaces) the own data property `key` with the provided `ASSetPropFlags` flags.
+  pub fn define(&mut self, key: String, value: AvmValue<'gc>, flags: u16) -> () {
+    let mut property = AvmObjectProperty::data(value);
+    property.set_flags(flags);
     self.properties.insert(key, property);
   }
 
+  /// Deletes the own property `key`, returns `false` if it does not exist or is not deletable.
+  pub fn delete(&mut self, key: &str) -> bool {
+    match self.properties.get(key) {
+      Some(property) if property.deletable => {
+        self.properties.remove(key);
+        true
+      }
+      _ => false,
+    }
+  }
+
+  /// Returns the value of the property `key`, ignoring the SWF-version visibility flags.
+  ///
+  /// The whole prototype chain is visited: the depth limit of the VM only applies to the lookups
+  /// performed by the actions.
   pub fn get(&self, key: &str) -> Option<AvmValue<'gc>> {
-    let mut result: Option<AvmValue<'gc>> = self.get_local(key);
-    // TODO: Recurse (needs loop detection?)
-    if result.is_none() {
-      result = match &self.prototype {
-        AvmObjectPrototype::Null(_) => result,
-        AvmObjectPrototype::Object(p) => p.0.borrow().get_local(key),
-      };
+    self.get_versioned(key, u8::MAX, usize::MAX)
+  }
+
+  /// Returns the value of the property `key` as seen by a movie with the provided SWF version,
+  /// visiting at most `max_depth` prototypes.
+  pub fn get_versioned(&self, key: &str, swf_version: u8, max_depth: usize) -> Option<AvmValue<'gc>> {
+    if let Some(value) = self.get_local_versioned(key, swf_version) {
+      return Some(value);
+    }
+    self.prototypes(max_depth)
+      .iter()
+      .filter_map(|p| p.0.borrow().get_local_versioned(key, swf_version))
+      .next()
+  }
+
+  /// Returns a copy of the property `key`, found on this object or the first `max_depth` objects
+  /// of its prototype chain.
+  pub fn find_property(&self, key: &str, swf_version: u8, max_depth: usize) -> Option<AvmObjectProperty<'gc>> {
+    if key == "__proto__" {
+      return self.get_local(key).map(AvmObjectProperty::data);
+    }
+    if let Some(property) = self.get_local_property(key).filter(|p| p.is_visible(swf_version)) {
+      return Some(property.clone());
+    }
+    self.prototypes(max_depth)
+      .iter()
+      .filter_map(|p| p.0.borrow().get_local_property(key).filter(|p| p.is_visible(swf_version)).cloned())
+      .next()
+  }
+
+  /// Returns the objects of the prototype chain, starting with the prototype of this object.
+  ///
+  /// The traversal stops on cycles (`a.__proto__ = a`) and after `max_depth` objects.
+  pub fn prototypes(&self, max_depth: usize) -> Vec<AvmObjectRef<'gc>> {
+    let mut result: Vec<AvmObjectRef<'gc>> = Vec::new();
+    let mut visited: Vec<*const AvmObject<'gc>> = vec![self as *const AvmObject<'gc>];
+    let mut next: AvmObjectPrototype<'gc> = self.prototype.clone();
+    while let AvmObjectPrototype::Object(p) = next {
+      if result.len() >= max_depth {
+        break;
+      }
+      let ptr: *const AvmObject<'gc> = p.0.borrow().deref();
+      if visited.contains(&ptr) {
+        break;
+      }
+      visited.push(ptr);
+      next = p.0.borrow().prototype.clone();
+      result.push(p);
     }
     result
   }
 
+  /// Tests if the property `key` exists on this object or the first `max_depth` objects of its
+  /// prototype chain.
+  pub fn has_property(&self, key: &str, swf_version: u8, max_depth: usize) -> bool {
+    self.has_own_property(key, swf_version)
+      || self.prototypes(max_depth).iter().any(|p| p.0.borrow().has_own_property(key, swf_version))
+  }
+
+  pub fn has_own_property(&self, key: &str, swf_version: u8) -> bool {
+    key == "__proto__" || self.get_local_property(key).is_some_and(|p| p.is_visible(swf_version))
+  }
+
+  /// Returns the keys visited by `for..in`: enumerable properties of the object and the first
+  /// `max_depth` objects of its prototype chain, without the shadowed ones.
+  ///
+  /// As in Flash Player, the own properties come first, from the most recently added one, then the
+  /// properties of each prototype in the same order.
+  pub fn enumerable_keys(&self, swf_version: u8, max_depth: usize) -> Vec<String> {
+    let mut keys: Vec<String> = Vec::new();
+    let mut shadowed: Vec<String> = Vec::new();
+    let mut collect = |properties: &PropertyMap<'gc>| {
+      for (key, property) in properties.iter().rev() {
+        if !property.is_visible(swf_version) || shadowed.contains(key) {
+          continue;
+        }
+        shadowed.push(key.clone());
+        if property.enumerable {
+          keys.push(key.clone());
+        }
+      }
+    };
+    collect(&self.properties);
+    for p in self.prototypes(max_depth) {
+      collect(&p.0.borrow().properties);
+    }
+    keys
+  }
+
+  /// Returns the value of the `length` property of an Array object.
+  pub fn array_length(&self) -> u32 {
+    match self.get_local("length") {
+      Some(AvmValue::Number(n)) => n.value() as u32,
+      _ => 0,
+    }
+  }
+
+  /// Updates the `length` of an Array object, deleting the indexed properties past the new length.
+  fn set_array_length(&mut self, value: AvmValue<'gc>) {
+    let length: f64 = match value {
+      AvmValue::Object(_) => 0f64,
+      ref v => v.to_avm_number().value(),
+    };
+    let length: u32 = if length.is_finite() && length > 0f64 { length.min(f64::from(u32::MAX - 1)) as u32 } else { 0 };
+    if length < self.array_length() {
+      self.properties.retain(|key, _| match array_index(key) {
+        Some(index) => index < length,
+        None => true,
+      });
+    }
+    let property = AvmObjectProperty {
+      enumerable: false,
+      deletable: false,
+      ..AvmObjectProperty::data(AvmValue::number(f64::from(length)))
+    };
+    self.properties.insert(String::from("length"), property);
+  }
+
   pub fn get_local(&self, key: &str) -> Option<AvmValue<'gc>> {
+    self.get_local_versioned(key, u8::MAX)
+  }
+
+  fn get_local_versioned(&self, key: &str, swf_version: u8) -> Option<AvmValue<'gc>> {
+    if key == "__proto__" {
+      return Some(match self.prototype {
+        AvmObjectPrototype::Null(_) => AvmValue::NULL,
+        AvmObjectPrototype::Object(ref p) => AvmValue::Object(p.clone()),
+      });
+    }
     self.properties.get(key)
+      .filter(|prop| prop.is_visible(swf_version))
       .map(|prop| prop.value.clone())
   }
 
@@ -112,16 +409,33 @@ impl<'gc> AvmObject<'gc> {
   }
 }
 
+/// Returns the array index represented by `key`, if any.
+///
+/// A key is an array index if it is the canonical string representation of an integer in
+/// `[0, 2^32 - 2]` (ES-262-3 section 15.4).
+pub(crate) fn array_index(key: &str) -> Option<u32> {
+  if key.is_empty() || (key.len() > 1 && key.starts_with('0')) || !key.bytes().all(|b| b.is_ascii_digit()) {
+    return None;
+  }
+  match key.parse::<u32>() {
+    Ok(index) if index != u32::MAX => Some(index),
+    _ => None,
+  }
+}
+
 #[derive(Debug, Clone, Trace)]
 pub struct AvmObjectRef<'gc>(pub Gc<'gc, GcRefCell<AvmObject<'gc>>>);
 
-//impl<'gc> AvmObjectRef<'gc> {
-//
-//}
+impl<'gc> AvmObjectRef<'gc> {
+  /// Tests if both references point to the same object.
+  pub fn ptr_eq(&self, other: &AvmObjectRef<'gc>) -> bool {
+    ::std::ptr::eq(&*self.0, &*other.0)
+  }
+}
 
 impl<'gc> AvmConvert<'gc> for AvmObjectRef<'gc> {
   fn to_avm_boolean(&self) -> AvmBoolean {
-    unimplemented!("ToBoolean(Object)")
+    AvmBoolean::TRUE
   }
 
   fn to_avm_number(&self) -> AvmNumber {
@@ -129,63 +443,51 @@ impl<'gc> AvmConvert<'gc> for AvmObjectRef<'gc> {
   }
 
   // ECMA 262-3 8.6.2.6: [[DefaultValue]] (hint)
-  fn to_avm_primitive<C: Context<'gc>>(&self, ctx: &mut C, hint: ToPrimitiveHint) -> Result<AvmPrimitive<'gc>, ()> {
-    let obj = &self.0;
-    match hint {
-      // TODO: `Date` objects use `Number` as the default hint
-      ToPrimitiveHint::Default | ToPrimitiveHint::String => {
-        // 1. Call the [[Get]] method of object O with argument "toString".
-        let to_string_method = obj.borrow().get("toString").unwrap_or(AvmValue::UNDEFINED);
-        // 2. If Result(1) is not an object, go to step 5.
-        match to_string_method {
-          v @ AvmValue::Object(_) => {
-            // 3. Call the [[Call]] method of Result(1), with O as the this value and an empty argument list.
-            let result = ctx.apply(v, AvmValue::Object(self.clone()), &[]).map_err(|_| ())?;
-            // 4. If Result(3) is a primitive value, return Result(3).
-            match AvmPrimitive::try_from(result) {
-              Ok(p) => return Ok(p),
-              Err(_) => {}
-            }
-          }
-          _ => {}
-        }
-        // 5. Call the [[Get]] method of object O with argument "valueOf".
-        let value_of_method = obj.borrow().get("valueOf").unwrap_or(AvmValue::UNDEFINED);
-        // 6. If Result(5) is not an object, go to step 9.
-        match value_of_method {
-          v @ AvmValue::Object(_) => {
-            // 7. Call the [[Call]] method of Result(5), with O as the this value and an empty argument list.
-            let result = ctx.apply(v, AvmValue::Object(self.clone()), &[]).map_err(|_| ())?;
-            // 8. If Result(7) is a primitive value, return Result(7).
-            match AvmPrimitive::try_from(result) {
-              Ok(p) => return Ok(p),
-              Err(_) => {}
-            }
-          }
-          _ => {}
+  //
+  // Exceptions thrown by `toString` or `valueOf` are returned as the error.
+  fn to_avm_primitive<C: Context<'gc> + ?Sized>(&self, ctx: &mut C, hint: ToPrimitiveHint) -> Result<AvmPrimitive<'gc>, AvmValue<'gc>> {
+    // With the `String` hint, `toString` is tried first, then `valueOf`. The order is reversed
+    // with the `Number` hint. `Date` objects use `Number` as the default hint.
+    let is_date = self.0.borrow().class == AvmObjectClass::Date;
+    let methods: [&str; 2] = match hint {
+      ToPrimitiveHint::Default if is_date => ["valueOf", "toString"],
+      ToPrimitiveHint::Default | ToPrimitiveHint::String => ["toString", "valueOf"],
+      ToPrimitiveHint::Number => ["valueOf", "toString"],
+    };
+    for method_name in methods.iter() {
+      // 1. Get the method (`toString` first with the `String` hint, `valueOf` first otherwise).
+      let method = self.0.borrow().get(method_name).unwrap_or(AvmValue::UNDEFINED);
+      // 2. If the method is not an object, try the next method.
+      if let v @ AvmValue::Object(_) = method {
+        // 3. Call the method with the object as `this` and an empty argument list.
+        let result = ctx.apply(v, AvmValue::Object(self.clone()), &[])?;
+        // 4. If the result is a primitive value, return it.
+        if let Ok(p) = AvmPrimitive::try_from(result) {
+          return Ok(p);
         }
-        // 9. Throw a TypeError exception.
-        Err(()) // AvmValue::String(ctx.string(String::from("TypeError")).unwrap()))
-      }
-      ToPrimitiveHint::Number => {
-        unimplemented!("ToPrimitive(Object, Hint::Number)")
       }
+      // 5-8. Same steps with the second method (next iteration)
     }
+    // 9. Neither method returned a primitive. Flash Player does not throw a TypeError but uses
+    // the type of the object instead.
+    let is_function = self.0.borrow().callable.is_some();
+    let name = if is_function { "[type Function]" } else { "[type Object]" };
+    Ok(AvmPrimitive::String(ctx.string(String::from(name)).unwrap()))
   }
 
-  fn to_avm_string<C: Context<'gc>>(&self, ctx: &mut C) -> Result<Gc<'gc, AvmString>, GcAllocErr> {
-    let primitive = self.to_avm_primitive(ctx, ToPrimitiveHint::String).unwrap();
+  fn to_avm_string<C: Context<'gc> + ?Sized>(&self, ctx: &mut C) -> Result<Gc<'gc, AvmString>, AvmValue<'gc>> {
+    let primitive = self.to_avm_primitive(ctx, ToPrimitiveHint::String)?;
     primitive.to_avm_string(ctx)
   }
 }
 
-#[derive(Debug, Trace)]
+#[derive(Debug, Clone, Trace)]
 pub enum AvmCallable<'gc> {
   AvmFunction(AvmFunction<'gc>),
   HostFunction(HostFunction<'gc>),
 }
 
-#[derive(Debug, Trace)]
+#[derive(Debug, Clone, Trace)]
 pub struct AvmFunction<'gc> {
   /// Id of the script containing the code
   //  script_id: Avm1ScriptId,
@@ -198,8 +500,42 @@ pub struct AvmFunction<'gc> {
   pub scope: Gc<'gc, GcRefCell<Scope<'gc>>>,
 
   pub register_count: u8,
+
+  pub parameters: Vec<AvmFunctionParameter>,
+
+  /// Target of the script defining this function
+  pub target: Option<TargetId>,
+
+  pub flags: AvmFunctionFlags,
+}
+
+/// Flags of `DefineFunction2` for the implicit values of a call.
+///
+/// The `preload_*` flags store the values in the registers following register `0`, in the field
+/// order. The `suppress_*` flags skip the local variables `this`, `arguments` and `super`.
+/// Functions defined with `DefineFunction` use the default flags.
+#[derive(Debug, Clone, Default, Trace)]
+pub struct AvmFunctionFlags {
+  pub preload_this: bool,
+  pub suppress_this: bool,
+  pub preload_arguments: bool,
+  pub suppress_arguments: bool,
+  pub preload_super: bool,
+  pub suppress_super: bool,
+  pub preload_root: bool,
+  pub preload_parent: bool,
+  pub preload_global: bool,
+}
+
+#[derive(Debug, Clone, Trace)]
+pub struct AvmFunctionParameter {
+  pub name: String,
+
+  /// Register receiving the argument, or `None` to bind it as a local variable.
+  pub register: Option<u8>,
 }
 
+#[derive(Clone)]
 pub struct HostFunction<'gc> {
   pub func: fn(&mut dyn CallContext<'gc>) -> AvmResult<'gc>,
 }
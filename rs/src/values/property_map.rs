@@ -0,0 +1,102 @@
+use ::std::collections::hash_map::HashMap;
+
+use crate::values::object::AvmObjectProperty;
+
+/// Own properties of an object, iterated in insertion order.
+///
+/// Replacing the value of an existing key keeps its position.
+#[derive(Debug, Clone, Default, Trace)]
+pub struct PropertyMap<'gc> {
+  entries: Vec<PropertyEntry<'gc>>,
+
+  /// Position of each key in `entries`
+  indices: HashMap<String, usize>,
+}
+
+#[derive(Debug, Clone, Trace)]
+struct PropertyEntry<'gc> {
+  key: String,
+  property: AvmObjectProperty<'gc>,
+}
+
+impl<'gc> PropertyMap<'gc> {
+  pub fn new() -> PropertyMap<'gc> {
+    PropertyMap { entries: Vec::new(), indices: HashMap::new() }
+  }
+
+  pub fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+
+  pub fn contains_key(&self, key: &str) -> bool {
+    self.indices.contains_key(key)
+  }
+
+  pub fn get(&self, key: &str) -> Option<&AvmObjectProperty<'gc>> {
+    self.indices.get(key).map(|&i| &self.entries[i].property)
+  }
+
+  pub fn get_mut(&mut self, key: &str) -> Option<&mut AvmObjectProperty<'gc>> {
+    match self.indices.get(key) {
+      Some(&i) => Some(&mut self.entries[i].property),
+      None => None,
+    }
+  }
+
+  /// Inserts or replaces the property `key`, returns the replaced property.
+  pub fn insert(&mut self, key: String, property: AvmObjectProperty<'gc>) -> Option<AvmObjectProperty<'gc>> {
+    match self.indices.get(&key) {
+      Some(&i) => Some(::std::mem::replace(&mut self.entries[i].property, property)),
+      None => {
+        self.indices.insert(key.clone(), self.entries.len());
+        self.entries.push(PropertyEntry { key, property });
+        None
+      }
+    }
+  }
+
+  pub fn remove(&mut self, key: &str) -> Option<AvmObjectProperty<'gc>> {
+    let index = self.indices.remove(key)?;
+    let entry = self.entries.remove(index);
+    for entry in &self.entries[index..] {
+      *self.indices.get_mut(&entry.key).unwrap() -= 1;
+    }
+    Some(entry.property)
+  }
+
+  /// Keeps only the properties for which `f` returns `true`, preserving their order.
+  pub fn retain<F: FnMut(&str, &AvmObjectProperty<'gc>) -> bool>(&mut self, mut f: F) -> () {
+    self.entries.retain(|entry| f(&entry.key, &entry.property));
+    self.indices = self.entries
+      .iter()
+      .enumerate()
+      .map(|(i, entry)| (entry.key.clone(), i))
+      .collect();
+  }
+
+  /// Iterates over the properties, in insertion order.
+  pub fn iter(&self) -> Iter<'_, 'gc> {
+    Iter(self.entries.iter())
+  }
+}
+
+/// Iterator over the keys and properties of a `PropertyMap`, in insertion order.
+pub struct Iter<'a, 'gc: 'a>(::std::slice::Iter<'a, PropertyEntry<'gc>>);
+
+impl<'a, 'gc: 'a> Iterator for Iter<'a, 'gc> {
+  type Item = (&'a String, &'a AvmObjectProperty<'gc>);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.0.next().map(|entry| (&entry.key, &entry.property))
+  }
+}
+
+impl<'a, 'gc: 'a> DoubleEndedIterator for Iter<'a, 'gc> {
+  fn next_back(&mut self) -> Option<Self::Item> {
+    self.0.next_back().map(|entry| (&entry.key, &entry.property))
+  }
+}
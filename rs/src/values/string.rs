@@ -1,7 +1,7 @@
 use ::scoped_gc::{Gc, GcAllocErr, GcScope};
 
 use crate::context::Context;
-use crate::values::{AvmBoolean, AvmConvert, AvmNumber, AvmPrimitive, ToPrimitiveHint};
+use crate::values::{AvmBoolean, AvmConvert, AvmNumber, AvmPrimitive, AvmValue, ToPrimitiveHint};
 
 #[derive(Debug, Eq, PartialEq, Clone, Trace)]
 pub struct AvmString(String);
@@ -17,19 +17,45 @@ impl AvmString {
 }
 
 impl<'gc> AvmConvert<'gc> for AvmString {
+  // Uses the SWF7+ semantics: any non-empty string is truthy
   fn to_avm_boolean(&self) -> AvmBoolean {
-    unimplemented!("ToBoolean(String)")
+    AvmBoolean::new(!self.0.is_empty())
   }
 
   fn to_avm_number(&self) -> AvmNumber {
-    unimplemented!("ToNumber(String)")
+    AvmNumber::new(string_to_number(&self.0))
   }
 
-  fn to_avm_primitive<C: Context<'gc>>(&self, _: &mut C, _: ToPrimitiveHint) -> Result<AvmPrimitive<'gc>, ()> {
+  fn to_avm_primitive<C: Context<'gc> + ?Sized>(&self, _: &mut C, _: ToPrimitiveHint) -> Result<AvmPrimitive<'gc>, AvmValue<'gc>> {
     unimplemented!("ToPrimitive(String)")
   }
 
-  fn to_avm_string<C: Context<'gc>>(&self, ctx: &mut C) -> Result<Gc<'gc, AvmString>, GcAllocErr> {
-    ctx.string(self.0.clone())
+  fn to_avm_string<C: Context<'gc> + ?Sized>(&self, ctx: &mut C) -> Result<Gc<'gc, AvmString>, AvmValue<'gc>> {
+    Ok(ctx.string(self.0.clone()).unwrap())
   }
 }
+
+/// Parses a string using the Flash Player rules (SWF7+).
+///
+/// Leading whitespace is ignored but trailing characters are not: `" 12"` is `12` but `"12 "` is
+/// `NaN`. Hexadecimal integers use the `0x` prefix. The empty string is `NaN`.
+fn string_to_number(value: &str) -> f64 {
+  let value = value.trim_start();
+  if value.is_empty() {
+    return f64::NAN;
+  }
+  if value.starts_with("0x") || value.starts_with("0X") {
+    return match i64::from_str_radix(&value[2..], 16) {
+      Ok(n) => n as f64,
+      Err(_) => f64::NAN,
+    };
+  }
+  // Rust also accepts `inf` and `NaN`, reject anything that is not a decimal literal
+  let is_decimal = value
+    .chars()
+    .all(|c| c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || c == '+' || c == '-');
+  if !is_decimal {
+    return f64::NAN;
+  }
+  value.parse::<f64>().unwrap_or(f64::NAN)
+}